@@ -0,0 +1,122 @@
+//! Compares finding the objects near the player by scanning every object against querying the
+//! `SpatialHash`, on a level of 10,000 tiles, and measures keeping the grid up to date while only the
+//! player moves. Run with `cargo bench`.
+
+use amethyst::{
+    ecs::{Entity, Join, RunNow, System},
+    prelude::*,
+};
+use amethyst_2d_platformer_demo::{
+    GROUND_TILE_SIZE, PLAYER_H, PLAYER_W,
+    components::{Player, TwoDimObject},
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    level::{Bounds, GroundSegment, Level, Point},
+    systems::{InputScript, PlayerInput, SpatialHash, SpatialHashSystem},
+};
+use criterion::{Criterion, criterion_group, criterion_main};
+
+const COLUMNS: usize = 200;
+const ROWS: usize = 50;
+
+/// 200 x 50 tiles, with the player standing on top of the middle column and kept above them.
+fn tile_level() -> LevelSource {
+    let ground = (0..COLUMNS)
+        .flat_map(|column| (0..ROWS).map(move |row| GroundSegment {
+            left: column as f32 * GROUND_TILE_SIZE,
+            top: (row + 1) as f32 * GROUND_TILE_SIZE,
+            width: GROUND_TILE_SIZE,
+            height: GROUND_TILE_SIZE,
+        }))
+        .collect();
+
+    LevelSource::Level(Level {
+        ground,
+        player_spawn: Point {
+            x: (COLUMNS / 2) as f32 * GROUND_TILE_SIZE,
+            y: ROWS as f32 * GROUND_TILE_SIZE + PLAYER_H as f32 / 2.,
+        },
+        bounds: Bounds {
+            left: 0.,
+            right: COLUMNS as f32 * GROUND_TILE_SIZE,
+            bottom: 0.,
+            top: (ROWS * 2) as f32 * GROUND_TILE_SIZE,
+        },
+        ..Level::default()
+    })
+}
+
+/// An area the size of the player, on top of the tiles.
+fn player_area() -> TwoDimObject {
+    let mut area = TwoDimObject::new(PLAYER_W as f32, PLAYER_H as f32);
+    area.set_left((COLUMNS / 2) as f32 * GROUND_TILE_SIZE);
+    area.set_bottom(ROWS as f32 * GROUND_TILE_SIZE);
+    area
+}
+
+fn touching(a: &TwoDimObject, b: &TwoDimObject) -> bool {
+    a.left() <= b.right() && b.left() <= a.right() && a.bottom() <= b.top() && b.bottom() <= a.top()
+}
+
+fn queries(c: &mut Criterion) {
+    let mut simulation = Simulation::new(&tile_level(), MovementConfig::default(), InputScript::new());
+    // one frame fills the spatial hash
+    simulation.step(1);
+    let area = player_area();
+
+    c.bench_function("scan every object (10k tiles)", move |b| b.iter(|| {
+        let world = simulation.world();
+        let entities = world.entities();
+        let two_dim_objects = world.read_storage::<TwoDimObject>();
+        (&entities, &two_dim_objects).join()
+            .filter(|(_, two_dim_object)| touching(&area, two_dim_object))
+            .map(|(entity, _)| entity)
+            .collect::<Vec<Entity>>()
+    }));
+
+    let mut simulation = Simulation::new(&tile_level(), MovementConfig::default(), InputScript::new());
+    simulation.step(1);
+    let area = player_area();
+
+    c.bench_function("query spatial hash (10k tiles)", move |b| b.iter(|| {
+        let world = simulation.world();
+        let two_dim_objects = world.read_storage::<TwoDimObject>();
+        world.read_resource::<SpatialHash>().query(area.left(), area.bottom(), area.right(), area.top())
+            .into_iter()
+            .filter(|&entity| two_dim_objects.get(entity).is_some_and(|two_dim_object| touching(&area, two_dim_object)))
+            .collect::<Vec<Entity>>()
+    }));
+}
+
+/// Nudges the player back and forth, and brings the grid up to date with it.
+fn upkeep(c: &mut Criterion) {
+    let mut simulation = Simulation::new(&tile_level(), MovementConfig::default(), InputScript::new());
+    simulation.step(1);
+    let mut system = SpatialHashSystem::default();
+    system.setup(&mut simulation.world_mut().res);
+    let mut offset = GROUND_TILE_SIZE;
+
+    c.bench_function("spatial hash upkeep per frame (10k tiles)", move |b| b.iter(|| {
+        {
+            let world = simulation.world();
+            let players = world.read_storage::<Player>();
+            let mut two_dim_objects = world.write_storage::<TwoDimObject>();
+            for (_, two_dim_object) in (&players, &mut two_dim_objects).join() {
+                two_dim_object.position.x += offset;
+            }
+        }
+        offset = -offset;
+        system.run_now(&simulation.world().res);
+    }));
+}
+
+fn simulation_frame(c: &mut Criterion) {
+    let script = InputScript::new().hold(PlayerInput { x: 1., jump: true, ..PlayerInput::default() }, usize::MAX);
+    let mut simulation = Simulation::new(&tile_level(), MovementConfig::default(), script);
+
+    c.bench_function("simulate one frame (10k tiles)", move |b| b.iter(|| simulation.step(1)));
+}
+
+criterion_group!(benches, queries, upkeep, simulation_frame);
+criterion_main!(benches);
\ No newline at end of file
@@ -0,0 +1,44 @@
+use amethyst::{
+    core::bundle::{Result, SystemBundle},
+    ecs::DispatcherBuilder,
+};
+use crate::systems::{
+    InputScript, PlayerInputSystem, ScriptedInputSystem, FixedTimestepSystem, ControlSystem, SpatialHashSystem,
+    PhysicsSystem, AnimationSystem, CameraFollowSystem,
+};
+
+/// Adds the platformer systems in dependency order.
+///
+/// By default the player is driven by the keyboard, which requires `InputBundle` to be added before
+/// this bundle. Use `with_input_script` to replay scripted input instead.
+#[derive(Default)]
+pub struct PlatformerBundle {
+    input_script: Option<InputScript>,
+}
+
+impl PlatformerBundle {
+    pub fn new() -> Self {
+        PlatformerBundle::default()
+    }
+
+    pub fn with_input_script(mut self, script: InputScript) -> Self {
+        self.input_script = Some(script);
+        self
+    }
+}
+
+impl<'a, 'b> SystemBundle<'a, 'b> for PlatformerBundle {
+    fn build(self, builder: &mut DispatcherBuilder<'a, 'b>) -> Result<()> {
+        match self.input_script {
+            Some(script) => builder.add(ScriptedInputSystem::new(script), "player_input_system", &[]),
+            None => builder.add(PlayerInputSystem, "player_input_system", &["input_system"]),
+        };
+        builder.add(FixedTimestepSystem, "fixed_timestep_system", &[]);
+        builder.add(SpatialHashSystem::default(), "spatial_hash_system", &[]);
+        builder.add(ControlSystem, "control_system", &["player_input_system", "fixed_timestep_system", "spatial_hash_system"]);
+        builder.add(PhysicsSystem, "physics_system", &["control_system", "spatial_hash_system"]);
+        builder.add(AnimationSystem, "animation_system", &["physics_system"]);
+        builder.add(CameraFollowSystem, "camera_follow_system", &["animation_system"]);
+        Ok(())
+    }
+}
@@ -0,0 +1,300 @@
+//! Swept axis-aligned box collision, shared by every rigid body `PhysicsSystem` moves.
+//!
+//! Bodies move along both axes at once and stop at the first edge they would cross, then slide along
+//! it for the rest of the move. Where along the move each obstacle would be touched is worked out
+//! exactly, so a fast body can't cut across the corner of a box or skip over a thin one. Obstacles can
+//! be static or moving, a moving obstacle is checked wherever it has got to so far this step. A body
+//! which starts within `CONTACT_EPSILON` of the far side of an edge, from rounding, still stops at it
+//! rather than passing through. Moves along a single axis, such as shoves, can use `sweep_x` and
+//! `sweep_y` instead.
+//!
+//! Slopes aren't swept against. A body moves as if they weren't there, then stands on the highest one
+//! under the middle of its bottom edge, as long as it started the step on or above it.
+
+use amethyst::ecs::Entity;
+use crate::components::{Contacts, Slope, TwoDimObject};
+
+/// How close two edges have to be to count as touching. Bodies are snapped exactly onto the edges
+/// they hit, but converting between edges and centers can leave a rounding error behind.
+pub const CONTACT_EPSILON: f32 = 0.01;
+
+/// Which edges of an obstacle stop a body.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Faces {
+    All,
+    /// Only the top, for bodies falling onto it.
+    Top,
+    /// Only the left and right.
+    Sides,
+    /// Only the bottom, for bodies rising into it.
+    Bottom,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+/// Where along a move a body first touches an obstacle.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Impact {
+    /// Fraction of the move made before touching, from 0 to 1.
+    pub time: f32,
+    /// The axis the edges meet along. The body can carry on sliding along the other one.
+    pub axis: Axis,
+}
+
+/// The obstacles a body hit while sweeping, along each axis.
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct Hits {
+    pub x: Option<Entity>,
+    pub y: Option<Entity>,
+}
+
+/// When a body moving by `distance_x` and `distance_y` first touches the obstacle, if it does at all
+/// during the move. Obstacles the body already overlaps by more than `CONTACT_EPSILON` are ignored, so
+/// it can get out of them.
+pub fn time_of_impact(body: &TwoDimObject, distance_x: f32, distance_y: f32, obstacle: &TwoDimObject) -> Option<Impact> {
+    let (enter_x, exit_x) = overlap_times(body.left(), body.right(), obstacle.left(), obstacle.right(), distance_x)?;
+    let (enter_y, exit_y) = overlap_times(body.bottom(), body.top(), obstacle.bottom(), obstacle.top(), distance_y)?;
+
+    // the boxes touch once they overlap along both axes, so the later axis decides. ties go to y, so
+    // a body landing exactly on a corner stands on it
+    let (enter, axis, distance) = if enter_y >= enter_x {
+        (enter_y, Axis::Y, distance_y)
+    } else {
+        (enter_x, Axis::X, distance_x)
+    };
+    let exit = exit_x.min(exit_y);
+    // a body which isn't moving can't run into anything
+    if !enter.is_finite() {
+        return None;
+    }
+    if enter > 1. || enter >= exit || enter * distance.abs() < -CONTACT_EPSILON {
+        return None;
+    }
+
+    Some(Impact { time: enter.max(0.), axis })
+}
+
+/// Fractions of a move at which a span starts and stops overlapping another, or `None` if it never
+/// does. A span which doesn't move overlaps for the whole move or not at all.
+fn overlap_times(min: f32, max: f32, other_min: f32, other_max: f32, distance: f32) -> Option<(f32, f32)> {
+    if distance > 0. {
+        Some(((other_min - max) / distance, (other_max - min) / distance))
+    } else if distance < 0. {
+        Some(((other_max - min) / distance, (other_min - max) / distance))
+    } else if min < other_max && other_min < max {
+        Some((f32::NEG_INFINITY, f32::INFINITY))
+    } else {
+        None
+    }
+}
+
+/// Moves the body by `distance_x` and `distance_y`, stopping at the first edge in the way and sliding
+/// along it with whatever is left of the move.
+pub fn sweep(body: &mut TwoDimObject, mut distance_x: f32, mut distance_y: f32, obstacles: &[(Entity, &TwoDimObject, Faces)]) -> Hits {
+    let mut hits = Hits::default();
+
+    // each hit stops the move along one axis, so there are at most two
+    for _ in 0..2 {
+        let mut first: Option<(Entity, &TwoDimObject, Impact)> = None;
+        for &(entity, obstacle, faces) in obstacles {
+            let impact = match time_of_impact(body, distance_x, distance_y, obstacle) {
+                Some(impact) => impact,
+                None => continue,
+            };
+            let stops = match (faces, impact.axis) {
+                (Faces::All, _) | (Faces::Sides, Axis::X) => true,
+                (Faces::Top, Axis::Y) => distance_y < 0.,
+                (Faces::Bottom, Axis::Y) => distance_y > 0.,
+                _ => false,
+            };
+            // keep going, a nearer obstacle may come later. of two at once, the one along y wins
+            let nearer = first.is_none_or(|(_, _, nearest)| {
+                (impact.time, impact.axis == Axis::X) < (nearest.time, nearest.axis == Axis::X)
+            });
+            if stops && nearer {
+                first = Some((entity, obstacle, impact));
+            }
+        }
+
+        let (entity, obstacle, impact) = match first {
+            Some(first) => first,
+            None => break,
+        };
+        body.position.x += distance_x * impact.time;
+        body.position.y += distance_y * impact.time;
+        match impact.axis {
+            Axis::X => {
+                // snap exactly onto the edge, so rounding doesn't leave the body inside or short of it
+                if distance_x > 0. { body.set_right(obstacle.left()) } else { body.set_left(obstacle.right()) }
+                distance_x = 0.;
+                distance_y *= 1. - impact.time;
+                hits.x = Some(entity);
+            },
+            Axis::Y => {
+                if distance_y > 0. { body.set_top(obstacle.bottom()) } else { body.set_bottom(obstacle.top()) }
+                distance_x *= 1. - impact.time;
+                distance_y = 0.;
+                hits.y = Some(entity);
+            },
+        }
+    }
+
+    body.position.x += distance_x;
+    body.position.y += distance_y;
+    hits
+}
+
+/// Moves the body `distance` along x, stopping against the nearest obstacle in the way.
+///
+/// Returns the obstacle hit, if any.
+pub fn sweep_x<'a, I>(body: &mut TwoDimObject, distance: f32, obstacles: I) -> Option<Entity>
+where
+    I: IntoIterator<Item = (Entity, &'a TwoDimObject)>,
+{
+    let mut hit = None;
+
+    if distance > 0. {
+        let old_x = body.right();
+        let mut new_x = old_x + distance;
+        for (entity, obstacle) in obstacles {
+            if body.overlapping_y(obstacle) && old_x <= obstacle.left() + CONTACT_EPSILON && new_x >= obstacle.left() {
+                // keep going, a nearer obstacle may come later
+                new_x = obstacle.left();
+                hit = Some(entity);
+            }
+        }
+        body.set_right(new_x);
+    } else if distance < 0. {
+        let old_x = body.left();
+        let mut new_x = old_x + distance;
+        for (entity, obstacle) in obstacles {
+            if body.overlapping_y(obstacle) && old_x >= obstacle.right() - CONTACT_EPSILON && new_x <= obstacle.right() {
+                new_x = obstacle.right();
+                hit = Some(entity);
+            }
+        }
+        body.set_left(new_x);
+    }
+
+    hit
+}
+
+/// Moves the body `distance` along y, stopping against the nearest obstacle in the way.
+///
+/// Returns the obstacle hit, if any.
+pub fn sweep_y<'a, I>(body: &mut TwoDimObject, distance: f32, obstacles: I) -> Option<Entity>
+where
+    I: IntoIterator<Item = (Entity, &'a TwoDimObject)>,
+{
+    let mut hit = None;
+
+    if distance > 0. {
+        let old_y = body.top();
+        let mut new_y = old_y + distance;
+        for (entity, obstacle) in obstacles {
+            if body.overlapping_x(obstacle) && old_y <= obstacle.bottom() + CONTACT_EPSILON && new_y >= obstacle.bottom() {
+                new_y = obstacle.bottom();
+                hit = Some(entity);
+            }
+        }
+        body.set_top(new_y);
+    } else if distance < 0. {
+        let old_y = body.bottom();
+        let mut new_y = old_y + distance;
+        for (entity, obstacle) in obstacles {
+            if body.overlapping_x(obstacle) && old_y >= obstacle.top() - CONTACT_EPSILON && new_y <= obstacle.top() {
+                new_y = obstacle.top();
+                hit = Some(entity);
+            }
+        }
+        body.set_bottom(new_y);
+    }
+
+    hit
+}
+
+/// Finds the obstacles whose edges the body is resting against.
+pub fn find_contacts<'a, I>(body: &TwoDimObject, obstacles: I) -> Contacts
+where
+    I: IntoIterator<Item = (Entity, &'a TwoDimObject)>,
+{
+    let mut contacts = Contacts::default();
+
+    for (entity, obstacle) in obstacles {
+        if body.overlapping_x(obstacle) {
+            if (body.bottom() - obstacle.top()).abs() <= CONTACT_EPSILON {
+                contacts.ground.get_or_insert(entity);
+            } else if (body.top() - obstacle.bottom()).abs() <= CONTACT_EPSILON {
+                contacts.ceiling.get_or_insert(entity);
+            }
+        } else if body.overlapping_y(obstacle) {
+            if (body.left() - obstacle.right()).abs() <= CONTACT_EPSILON {
+                contacts.left.get_or_insert(entity);
+            } else if (body.right() - obstacle.left()).abs() <= CONTACT_EPSILON {
+                contacts.right.get_or_insert(entity);
+            }
+        }
+    }
+
+    contacts
+}
+
+/// Whether two objects overlap by more than `CONTACT_EPSILON`, so bodies merely resting against each
+/// other don't count.
+pub fn penetrating(a: &TwoDimObject, b: &TwoDimObject) -> bool {
+    a.left() < b.right() - CONTACT_EPSILON && b.left() < a.right() - CONTACT_EPSILON
+        && a.bottom() < b.top() - CONTACT_EPSILON && b.bottom() < a.top() - CONTACT_EPSILON
+}
+
+/// Whether the body is inside the zone, counting a body standing exactly on top of it. That way a
+/// body which has climbed to the top of a ladder still holds on to it.
+pub fn within(body: &TwoDimObject, zone: &TwoDimObject) -> bool {
+    body.overlapping_x(zone) && body.bottom() <= zone.top() + CONTACT_EPSILON && zone.bottom() < body.top()
+}
+
+/// The part of a slope which stops bodies moving `distance` along x into it, a box as high as the end
+/// of the slope they come from. A body coming from the low end, or level with the high end, walks
+/// straight onto it.
+pub fn slope_wall(object: &TwoDimObject, slope: &Slope, distance: f32) -> TwoDimObject {
+    let mut wall = *object;
+    let height = if distance > 0. { slope.left_height } else { slope.right_height };
+    wall.size.y = (height - CONTACT_EPSILON).max(0.);
+    wall.set_bottom(object.bottom());
+    wall
+}
+
+/// Finds the highest slope the body should be standing on after moving from `previous`, along with
+/// the height its bottom edge should be at. Slopes whose top the body started below are ignored, so it
+/// can pass underneath them. With `sticky` set, for bodies which were on the ground and aren't rising,
+/// a slope falling away under the body still catches it, so it follows the slope down instead of
+/// walking off into the air.
+pub fn find_slope<'a, I>(body: &TwoDimObject, previous: &TwoDimObject, sticky: bool, slopes: I) -> Option<(Entity, f32)>
+where
+    I: IntoIterator<Item = (Entity, &'a TwoDimObject, &'a Slope)>,
+{
+    let mut highest: Option<(Entity, f32)> = None;
+
+    for (entity, object, slope) in slopes {
+        if !body.overlapping_x(object)
+            || previous.bottom() < slope.surface_at(object, previous.position.x) - CONTACT_EPSILON {
+            continue;
+        }
+
+        let surface = slope.surface_at(object, body.position.x);
+        // enough to keep up with the slope after stepping off the edge of whatever the body stood on
+        let snap = if sticky {
+            (body.size.x / 2. + (body.position.x - previous.position.x).abs()) * slope.gradient(object).abs()
+        } else {
+            0.
+        };
+        if body.bottom() <= surface + snap + CONTACT_EPSILON && highest.is_none_or(|(_, height)| surface > height) {
+            highest = Some((entity, surface));
+        }
+    }
+
+    highest
+}
\ No newline at end of file
@@ -1,15 +1,27 @@
 use amethyst::{
     core::{Transform},
-    ecs::{Component, VecStorage},
+    ecs::{Component, Entity, FlaggedStorage, NullStorage, VecStorage},
 };
 use specs_derive::Component;
 
-#[derive(PartialEq, Clone, Copy)]
+use crate::level::PathMode;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
 pub enum PlayerState {
     Idle,
     Walking,
     Running,
-    Jumping,
+    Rising,
+    /// Around the top of a jump, neither clearly rising nor falling.
+    Apex,
+    Falling,
+    /// Briefly, on touching the ground after being in the air.
+    Landing,
+    WallSlide,
+    AirJumping,
+    Climbing,
+    Crouching,
+    Dashing,
 }
 
 impl Default for PlayerState {
@@ -18,6 +30,14 @@ impl Default for PlayerState {
     }
 }
 
+impl PlayerState {
+    /// Whether the state is only ever entered in the air.
+    pub fn is_airborne(self) -> bool {
+        matches!(self, PlayerState::Rising | PlayerState::Apex | PlayerState::Falling | PlayerState::WallSlide | PlayerState::AirJumping)
+    }
+}
+
+#[derive(Clone, Copy)]
 pub struct TwoDimVector<T> {
     pub x: T,
     pub y: T,
@@ -32,12 +52,80 @@ impl Default for TwoDimVector<f32> {
     }
 }
 
-#[derive(Component)]
-#[storage(VecStorage)]
+/// What a body touched during the last physics step, on each side.
+#[derive(Default, Clone, Copy, PartialEq, Debug)]
+pub struct Contacts {
+    pub ground: Option<Entity>,
+    pub ceiling: Option<Entity>,
+    pub left: Option<Entity>,
+    pub right: Option<Entity>,
+}
+
+impl Contacts {
+    pub fn on_ground(&self) -> bool {
+        self.ground.is_some()
+    }
+
+    pub fn on_wall(&self) -> bool {
+        self.left.is_some() || self.right.is_some()
+    }
+
+    /// Whether moving sideways at `velocity_x` pushes into a wall the body is touching.
+    pub fn pushing_into_wall(&self, velocity_x: f32) -> bool {
+        (self.left.is_some() && velocity_x < 0.) || (self.right.is_some() && velocity_x > 0.)
+    }
+}
+
+/// An axis-aligned box other bodies collide with. On its own it stays where it was placed, add
+/// `RigidBody` and `Velocity` to have `PhysicsSystem` move it.
+#[derive(Clone, Copy)]
 pub struct TwoDimObject {
     pub size: TwoDimVector<f32>,
     pub position: TwoDimVector<f32>,
-    pub velocity: TwoDimVector<f32>,
+    /// Position at the start of the last physics step, used to interpolate rendering between steps.
+    pub previous_position: TwoDimVector<f32>,
+    /// Filled in by `PhysicsSystem` for rigid bodies.
+    pub contacts: Contacts,
+}
+
+/// Changes are flagged so `SpatialHashSystem` only has to look at objects which moved.
+impl Component for TwoDimObject {
+    type Storage = FlaggedStorage<Self, VecStorage<Self>>;
+}
+
+/// Makes a `TwoDimObject` move by its `Velocity`, fall under gravity and stop against other objects.
+#[derive(Component, Clone, Copy, Debug)]
+#[storage(VecStorage)]
+pub struct RigidBody {
+    /// Multiplies the gravity in `MovementConfig`, 0 for bodies which fly straight such as projectiles.
+    pub gravity_scale: f32,
+    /// Lets the body fall through `OneWayPlatform`s. Cleared by `PhysicsSystem` once the body is
+    /// clear of them.
+    pub dropping_through: bool,
+    /// Holding on to a `Climbable`, which suspends gravity and lets the body pass through
+    /// `OneWayPlatform`s. Cleared by `PhysicsSystem` once the body is outside every climbable.
+    pub climbing: bool,
+    /// Dashing in a straight line, which suspends gravity.
+    pub dashing: bool,
+}
+
+impl Default for RigidBody {
+    fn default() -> Self {
+        RigidBody {
+            gravity_scale: 1.,
+            dropping_through: false,
+            climbing: false,
+            dashing: false,
+        }
+    }
+}
+
+/// In units per second.
+#[derive(Component, Default, Clone, Copy, PartialEq, Debug)]
+#[storage(VecStorage)]
+pub struct Velocity {
+    pub x: f32,
+    pub y: f32,
 }
 
 impl TwoDimObject {
@@ -45,16 +133,14 @@ impl TwoDimObject {
         TwoDimObject {
             size: TwoDimVector { x: width, y: height },
             position: TwoDimVector { x: 0., y: 0. },
-            velocity: TwoDimVector { x: 0., y: 0. },
+            previous_position: TwoDimVector { x: 0., y: 0. },
+            contacts: Contacts::default(),
         }
     }
 
     pub fn set_position(&mut self, x: f32, y: f32) {
         self.position = TwoDimVector { x, y };
-    }
-
-    pub fn set_velocity(&mut self, x: f32, y: f32) {
-        self.velocity = TwoDimVector { x, y };
+        self.previous_position = self.position;
     }
 
     pub fn update_transform_position(&self, transform: &mut Transform) {
@@ -62,6 +148,11 @@ impl TwoDimObject {
         transform.set_y(self.position.y);
     }
 
+    pub fn update_transform_position_interpolated(&self, transform: &mut Transform, alpha: f32) {
+        transform.set_x(self.previous_position.x + (self.position.x - self.previous_position.x) * alpha);
+        transform.set_y(self.previous_position.y + (self.position.y - self.previous_position.y) * alpha);
+    }
+
     pub fn top(&self) -> f32 {
         self.position.y + self.size.y / 2.
     }
@@ -103,20 +194,228 @@ impl TwoDimObject {
     }
 }
 
+/// Player controlled behaviour, on top of the entity's `TwoDimObject`, `RigidBody` and `Velocity`.
 #[derive(Component)]
 #[storage(VecStorage)]
 pub struct Player {
     pub ticks: usize,
     pub state: PlayerState,
-    pub two_dim: TwoDimObject,
+    /// Physics steps since the player last stood on something, 0 while on the ground.
+    pub steps_since_grounded: u32,
+    /// Physics steps left in which an earlier jump press will still make the player jump.
+    pub jump_buffer: u32,
+    /// Whether the player is rising from a jump that can still be cut short by releasing jump.
+    pub jump_rising: bool,
+    /// Whether jump was held during the last frame, to tell new presses apart from holding it.
+    pub jump_held: bool,
+    /// Mid-air jumps left before the player has to land again.
+    pub air_jumps_left: u32,
+    /// Set when the player makes a mid-air jump, until the animation has picked it up.
+    pub air_jump_started: bool,
+    /// Physics steps left in which horizontal input is ignored after a wall jump.
+    pub wall_jump_lock: u32,
+    /// Set by `ControlSystem` while the player is holding down on the ground.
+    pub wants_to_crouch: bool,
+    /// Whether the player's body is shrunk to the crouching height. `PhysicsSystem` only stands the
+    /// player back up once there is room overhead.
+    pub crouching: bool,
+    /// 1 when facing right and -1 when facing left, the direction a dash goes without horizontal input.
+    pub facing: f32,
+    /// Whether dash was held during the last frame, to tell new presses apart from holding it.
+    pub dash_held: bool,
+    /// Physics steps left in the current dash, 0 when not dashing.
+    pub dash_steps_left: u32,
+    /// Physics steps left before the player can dash again.
+    pub dash_cooldown: u32,
+    /// Whether the player can still dash before landing again.
+    pub air_dash_available: bool,
 }
 
-impl Player {
-    pub fn new(two_dim: TwoDimObject) -> Self {
+impl Default for Player {
+    fn default() -> Self {
         Player {
             ticks: 0,
             state: PlayerState::Idle,
-            two_dim,
+            steps_since_grounded: u32::MAX,
+            jump_buffer: 0,
+            jump_rising: false,
+            jump_held: false,
+            air_jumps_left: 0,
+            air_jump_started: false,
+            wall_jump_lock: 0,
+            wants_to_crouch: false,
+            crouching: false,
+            facing: 1.,
+            dash_held: false,
+            dash_steps_left: 0,
+            dash_cooldown: 0,
+            air_dash_available: false,
         }
     }
+}
+
+/// Marks entities spawned from the level file, so they can be replaced when the file changes.
+#[derive(Component, Default)]
+#[storage(NullStorage)]
+pub struct LevelEntity;
+
+/// Makes a `TwoDimObject` only solid to bodies landing on its top, so they can jump up through it
+/// and walk through its sides.
+#[derive(Component, Default)]
+#[storage(NullStorage)]
+pub struct OneWayPlatform;
+
+/// Turns a `TwoDimObject` into a ramp. Its top is a straight line from `left_height` above the
+/// bottom left corner to `right_height` above the bottom right corner, and only the part under the
+/// line is solid. Bodies stand on it with the middle of their bottom edge.
+#[derive(Component, Clone, Copy, PartialEq, Debug)]
+#[storage(VecStorage)]
+pub struct Slope {
+    pub left_height: f32,
+    pub right_height: f32,
+}
+
+impl Slope {
+    /// Height of the top of the slope at `x`. Past either end it stays at the height of that end.
+    pub fn surface_at(&self, object: &TwoDimObject, x: f32) -> f32 {
+        let along = ((x - object.left()) / object.size.x).min(1.).max(0.);
+        object.bottom() + self.left_height + (self.right_height - self.left_height) * along
+    }
+
+    /// How much the top rises for each unit moved right, negative if it falls.
+    pub fn gradient(&self, object: &TwoDimObject) -> f32 {
+        (self.right_height - self.left_height) / object.size.x
+    }
+}
+
+/// Lets players shove a rigid body sideways by walking into it. Anything stacked on top of it is
+/// carried along.
+#[derive(Component, Default)]
+#[storage(NullStorage)]
+pub struct Pushable;
+
+/// Makes a `TwoDimObject` a ladder or other area the player can climb around in, instead of a solid
+/// box.
+#[derive(Component, Default)]
+#[storage(NullStorage)]
+pub struct Climbable;
+
+/// Where a `MovingPlatform` is along its path.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PathProgress {
+    /// Index of the waypoint the platform is heading to.
+    pub target: usize,
+    /// Whether a ping-pong path is being followed from first to last waypoint, rather than back.
+    pub forward: bool,
+    /// Physics steps left to wait at the waypoint the platform reached last.
+    pub wait_left: u32,
+}
+
+/// Moves the entity's `TwoDimObject` along waypoints, carrying any bodies standing on it and pushing
+/// any in its way.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct MovingPlatform {
+    /// Positions of the platform's top left corner, starting at the first.
+    pub waypoints: Vec<TwoDimVector<f32>>,
+    pub mode: PathMode,
+    /// In units per second.
+    pub speed: f32,
+    /// Physics steps to wait at each waypoint.
+    pub wait_steps: u32,
+    pub progress: PathProgress,
+}
+
+impl MovingPlatform {
+    pub fn new(waypoints: Vec<TwoDimVector<f32>>, mode: PathMode, speed: f32, wait_steps: u32) -> Self {
+        MovingPlatform {
+            progress: PathProgress {
+                target: 1 % waypoints.len().max(1),
+                forward: true,
+                wait_left: wait_steps,
+            },
+            waypoints,
+            mode,
+            speed,
+            wait_steps,
+        }
+    }
+
+    /// Where the top left corner of the platform will be after moving from `from` for `dt` seconds,
+    /// and how far along the path it will be then. Nothing changes until the caller applies both.
+    pub fn advance(&self, from: TwoDimVector<f32>, dt: f32) -> (TwoDimVector<f32>, PathProgress) {
+        let mut position = from;
+        let mut progress = self.progress;
+
+        if self.waypoints.len() < 2 {
+            return (position, progress);
+        }
+        if progress.wait_left > 0 {
+            progress.wait_left -= 1;
+            return (position, progress);
+        }
+
+        let mut distance = self.speed * dt;
+        // bounded, so a path whose waypoints are all in the same place can't loop forever
+        for _ in 0..self.waypoints.len() {
+            let target = self.waypoints[progress.target];
+            let (dx, dy) = (target.x - position.x, target.y - position.y);
+            let to_target = (dx * dx + dy * dy).sqrt();
+
+            if to_target > distance {
+                position.x += dx / to_target * distance;
+                position.y += dy / to_target * distance;
+                break;
+            }
+
+            position = target;
+            distance -= to_target;
+            progress = self.next_target(progress);
+            if self.wait_steps > 0 {
+                progress.wait_left = self.wait_steps;
+                break;
+            }
+        }
+
+        (position, progress)
+    }
+
+    fn next_target(&self, progress: PathProgress) -> PathProgress {
+        let last = self.waypoints.len() - 1;
+        let (target, forward) = match self.mode {
+            PathMode::Loop => ((progress.target + 1) % self.waypoints.len(), true),
+            PathMode::PingPong if progress.forward && progress.target < last => (progress.target + 1, true),
+            PathMode::PingPong if progress.forward => (progress.target - 1, false),
+            PathMode::PingPong if progress.target > 0 => (progress.target - 1, false),
+            PathMode::PingPong => (progress.target + 1, true),
+        };
+        PathProgress { target, forward, wait_left: 0 }
+    }
+}
+
+/// Moves the camera it is attached to so the target stays in view, without showing past the level
+/// bounds.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct CameraFollow {
+    pub target: Entity,
+    pub view_size: TwoDimVector<f32>,
+    /// Half the size of the area around the center of the view the target can move in freely.
+    pub dead_zone: TwoDimVector<f32>,
+    pub smoothing: f32,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum BarEdge {
+    /// Left bar when pillarboxing, bottom bar when letterboxing.
+    Start,
+    /// Right bar when pillarboxing, top bar when letterboxing.
+    End,
+}
+
+/// One of the two bars covering the parts of the window outside the camera's virtual resolution.
+#[derive(Component)]
+#[storage(VecStorage)]
+pub struct LetterboxBar {
+    pub edge: BarEdge,
 }
\ No newline at end of file
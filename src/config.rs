@@ -0,0 +1,204 @@
+use std::{error, fmt};
+
+use amethyst::{
+    assets::{self, Asset, Handle, ProcessingState},
+    config::{Config, ConfigError},
+    ecs::VecStorage,
+};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::PLAYER_H;
+
+/// Tuning for how the player moves, loaded from `resources/movement_config.ron`.
+///
+/// Velocities are in units per second and accelerations in units per second squared. Frames are
+/// fixed physics steps.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct MovementConfig {
+    pub max_x_velocity: f32,
+    pub x_acceleration: f32,
+    /// Height of a jump when the jump button is held until the top.
+    pub max_jump_height: f32,
+    /// Height of a jump when the jump button is only tapped.
+    pub min_jump_height: f32,
+    pub gravity: f32,
+    /// How long after walking off a ledge the player can still jump.
+    pub coyote_time_frames: u32,
+    /// How long before landing a jump press is remembered.
+    pub jump_buffer_frames: u32,
+    /// How many extra jumps the player can make before landing again.
+    pub air_jumps: u32,
+    /// Fastest the player falls while sliding down a wall.
+    pub wall_slide_velocity: f32,
+    /// Sideways velocity a wall jump pushes the player away from the wall with.
+    pub wall_jump_x_velocity: f32,
+    /// How long after a wall jump horizontal input is ignored, so the player can't steer straight
+    /// back into the wall.
+    pub wall_jump_lock_frames: u32,
+    /// Speed the player moves at in any direction while climbing.
+    pub climb_velocity: f32,
+    /// Height of the player's body while crouching, with their feet staying where they are. No taller
+    /// than the player standing.
+    pub crouch_height: f32,
+    /// Fastest the player moves sideways while crouching.
+    pub crouch_x_velocity: f32,
+    /// How far a dash carries the player.
+    pub dash_distance: f32,
+    /// How long a dash lasts, during which gravity and the usual speed limit don't apply.
+    pub dash_duration_frames: u32,
+    /// How long after a dash ends before the player can dash again.
+    pub dash_cooldown_frames: u32,
+}
+
+impl Default for MovementConfig {
+    fn default() -> Self {
+        MovementConfig {
+            max_x_velocity: 300.,
+            x_acceleration: 360.,
+            max_jump_height: 285.,
+            min_jump_height: 90.,
+            gravity: 2520.,
+            coyote_time_frames: 6,
+            jump_buffer_frames: 6,
+            air_jumps: 1,
+            wall_slide_velocity: 150.,
+            wall_jump_x_velocity: 300.,
+            wall_jump_lock_frames: 10,
+            climb_velocity: 200.,
+            crouch_height: 100.,
+            crouch_x_velocity: 100.,
+            dash_distance: 200.,
+            dash_duration_frames: 10,
+            dash_cooldown_frames: 30,
+        }
+    }
+}
+
+/// Loading the config as an asset lets it be hot-reloaded while the game is running.
+impl Asset for MovementConfig {
+    const NAME: &'static str = "platformer::MovementConfig";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl From<MovementConfig> for assets::Result<ProcessingState<MovementConfig>> {
+    fn from(config: MovementConfig) -> Self {
+        config.validate().map_err(|e| e.to_string())?;
+        Ok(ProcessingState::Loaded(config))
+    }
+}
+
+#[derive(Debug)]
+pub enum MovementConfigError {
+    Load(ConfigError),
+    NotFinite { field: &'static str },
+    NotPositive { field: &'static str },
+    Negative { field: &'static str },
+    MinAboveMax { min: &'static str, max: &'static str },
+    TooLarge { field: &'static str, max: f32 },
+}
+
+impl fmt::Display for MovementConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MovementConfigError::Load(e) => write!(f, "failed to load movement config: {}", e),
+            MovementConfigError::NotFinite { field } => write!(f, "{} must be a finite number", field),
+            MovementConfigError::NotPositive { field } => write!(f, "{} must be greater than zero", field),
+            MovementConfigError::Negative { field } => write!(f, "{} must not be negative", field),
+            MovementConfigError::MinAboveMax { min, max } => write!(f, "{} must not be greater than {}", min, max),
+            MovementConfigError::TooLarge { field, max } => write!(f, "{} must not be greater than {}", field, max),
+        }
+    }
+}
+
+impl error::Error for MovementConfigError {}
+
+impl From<ConfigError> for MovementConfigError {
+    fn from(e: ConfigError) -> Self {
+        MovementConfigError::Load(e)
+    }
+}
+
+impl MovementConfig {
+    pub fn load(path: &str) -> Result<Self, MovementConfigError> {
+        let config = MovementConfig::load_no_fallback(path)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), MovementConfigError> {
+        let fields = [
+            ("max_x_velocity", self.max_x_velocity),
+            ("x_acceleration", self.x_acceleration),
+            ("max_jump_height", self.max_jump_height),
+            ("min_jump_height", self.min_jump_height),
+            ("gravity", self.gravity),
+            ("wall_slide_velocity", self.wall_slide_velocity),
+            ("wall_jump_x_velocity", self.wall_jump_x_velocity),
+            ("climb_velocity", self.climb_velocity),
+            ("crouch_height", self.crouch_height),
+            ("crouch_x_velocity", self.crouch_x_velocity),
+            ("dash_distance", self.dash_distance),
+        ];
+        for &(field, value) in fields.iter() {
+            if !value.is_finite() {
+                return Err(MovementConfigError::NotFinite { field });
+            }
+        }
+
+        positive("max_x_velocity", self.max_x_velocity)?;
+        positive("x_acceleration", self.x_acceleration)?;
+        non_negative("max_jump_height", self.max_jump_height)?;
+        non_negative("min_jump_height", self.min_jump_height)?;
+        positive("gravity", self.gravity)?;
+        positive("wall_slide_velocity", self.wall_slide_velocity)?;
+        non_negative("wall_jump_x_velocity", self.wall_jump_x_velocity)?;
+        positive("climb_velocity", self.climb_velocity)?;
+        positive("crouch_height", self.crouch_height)?;
+        if self.crouch_height > PLAYER_H as f32 {
+            return Err(MovementConfigError::TooLarge { field: "crouch_height", max: PLAYER_H as f32 });
+        }
+        non_negative("crouch_x_velocity", self.crouch_x_velocity)?;
+        non_negative("dash_distance", self.dash_distance)?;
+        if self.dash_duration_frames == 0 {
+            return Err(MovementConfigError::NotPositive { field: "dash_duration_frames" });
+        }
+        if self.min_jump_height > self.max_jump_height {
+            return Err(MovementConfigError::MinAboveMax { min: "min_jump_height", max: "max_jump_height" });
+        }
+
+        Ok(())
+    }
+
+    /// Upward velocity which reaches `max_jump_height` under gravity.
+    pub fn jump_velocity(&self) -> f32 {
+        (2. * self.gravity * self.max_jump_height).sqrt()
+    }
+
+    /// Upward velocity a jump is cut to when the jump button is released, so a tap reaches about
+    /// `min_jump_height`.
+    pub fn jump_release_velocity(&self) -> f32 {
+        (2. * self.gravity * self.min_jump_height).sqrt()
+    }
+
+    /// Sideways velocity which covers `dash_distance` in `dash_duration_frames` physics steps.
+    pub fn dash_velocity(&self, step_seconds: f32) -> f32 {
+        self.dash_distance / (self.dash_duration_frames as f32 * step_seconds)
+    }
+}
+
+fn positive(field: &'static str, value: f32) -> Result<(), MovementConfigError> {
+    if value > 0. {
+        Ok(())
+    } else {
+        Err(MovementConfigError::NotPositive { field })
+    }
+}
+
+fn non_negative(field: &'static str, value: f32) -> Result<(), MovementConfigError> {
+    if value >= 0. {
+        Ok(())
+    } else {
+        Err(MovementConfigError::Negative { field })
+    }
+}
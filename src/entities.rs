@@ -1,94 +1,438 @@
 use amethyst::{
-    assets::{AssetStorage, Loader},
-    core::{Transform},
-    ecs::{Entity},
+    assets::{Asset, AssetStorage, Handle, Loader, RonFormat},
+    core::{Parent, Transform},
+    ecs::{Entity, Join},
     prelude::*,
     renderer::{
         Camera, PngFormat, Projection, Sprite, SpriteRender, SpriteSheet,
-        SpriteSheetHandle, Texture, TextureMetadata, SpriteSheetFormat, Transparent
+        SpriteSheetHandle, Texture, TextureData, TextureMetadata, SpriteSheetFormat, Transparent
     },
 };
 use crate::{
-    DISPLAY_WIDTH, PLAYER_W, PLAYER_H, CRATE_SIZE, GROUND_Y, TOTAL_PLAYER_SPRITE_HEIGHT,
-    components::{Player, TwoDimObject}
+    VIRTUAL_WIDTH, VIRTUAL_HEIGHT, PLAYER_W, PLAYER_H, CRATE_SIZE, GROUND_TILE_SIZE, TOTAL_PLAYER_SPRITE_HEIGHT, LEVEL_PATH,
+    MOVEMENT_CONFIG_PATH,
+    components::{BarEdge, CameraFollow, Climbable, Contacts, LetterboxBar, LevelEntity, MovingPlatform, OneWayPlatform, Player, Pushable, RigidBody, Slope, TwoDimObject, TwoDimVector, Velocity},
+    config::MovementConfig,
+    level::{Background, Bounds, CameraSettings, Crate, GroundSegment, Level, MovingSegment, Point, SlopeSegment},
+    tiled::{Collider, Tile, TiledMap, Tileset},
 };
 
-pub struct InitialState;
+const SLOPE_SPRITE_THICKNESS: f32 = 32.;
+
+pub enum LevelSource {
+    Level(Level),
+    TiledMap(TiledMap),
+}
+
+impl LevelSource {
+    pub fn player_spawn(&self) -> Point {
+        match self {
+            LevelSource::Level(level) => level.player_spawn,
+            LevelSource::TiledMap(map) => map.player_spawn,
+        }
+    }
+
+    pub fn bounds(&self) -> Bounds {
+        match self {
+            LevelSource::Level(level) => level.bounds,
+            LevelSource::TiledMap(map) => map.bounds,
+        }
+    }
+
+    pub fn camera_settings(&self) -> CameraSettings {
+        match self {
+            LevelSource::Level(level) => level.camera,
+            LevelSource::TiledMap(_) => CameraSettings::default(),
+        }
+    }
+}
+
+struct LevelSprites {
+    background: SpriteSheetHandle,
+    ground: SpriteSheetHandle,
+    crate_: SpriteSheetHandle,
+}
+
+pub struct InitialState {
+    source: LevelSource,
+    level_sprites: Option<LevelSprites>,
+    // the level and movement config files are watched through these, and applied when they change
+    level_handle: Option<Handle<Level>>,
+    movement_config_handle: Option<Handle<MovementConfig>>,
+    // versions of the assets last seen, which go up each time a file is reloaded
+    level_version: Option<u32>,
+    movement_config_version: Option<u32>,
+}
+
+impl InitialState {
+    pub fn new(source: LevelSource) -> Self {
+        InitialState {
+            source,
+            level_sprites: None,
+            level_handle: None,
+            movement_config_handle: None,
+            level_version: None,
+            movement_config_version: None,
+        }
+    }
+
+    fn reload_movement_config(&mut self, world: &mut World) {
+        let handle = match &self.movement_config_handle {
+            Some(handle) => handle,
+            None => return,
+        };
+
+        let storage = world.read_resource::<AssetStorage<MovementConfig>>();
+        let version = storage.get_version(handle);
+        if version == self.movement_config_version {
+            return;
+        }
+        self.movement_config_version = version;
+        let config = storage.get(handle).cloned();
+        drop(storage);
+
+        if let Some(config) = config {
+            *world.write_resource::<MovementConfig>() = config;
+        }
+    }
+
+    fn reload_level(&mut self, world: &mut World) {
+        let (handle, sprites) = match (&self.level_handle, &self.level_sprites) {
+            (Some(handle), Some(sprites)) => (handle, sprites),
+            _ => return,
+        };
+
+        let storage = world.read_resource::<AssetStorage<Level>>();
+        let version = storage.get_version(handle);
+        if version == self.level_version {
+            return;
+        }
+        // the first version loaded is the level the game started with, which is already built
+        let reloaded = self.level_version.is_some();
+        self.level_version = version;
+        let level = if reloaded { storage.get(handle).cloned() } else { None };
+        drop(storage);
+
+        if let (Some(level), LevelSource::Level(current_level)) = (level, &mut self.source) {
+            // the player is left alone, so only the layout around them changes
+            delete_level_entities(world);
+            init_level(world, &level, sprites);
+            world.add_resource(level.bounds);
+            for follow in (&mut world.write_storage::<CameraFollow>()).join() {
+                follow.dead_zone = TwoDimVector { x: level.camera.dead_zone.x, y: level.camera.dead_zone.y };
+                follow.smoothing = level.camera.smoothing;
+            }
+            // what the player was touching may have been deleted, and is found again next physics step
+            for (_, two_dim_object) in (&world.read_storage::<Player>(), &mut world.write_storage::<TwoDimObject>()).join() {
+                two_dim_object.contacts = Contacts::default();
+            }
+            *current_level = level;
+        }
+    }
+}
 
 impl SimpleState for InitialState {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
         let world = data.world;
+        world.add_resource(self.source.bounds());
 
-        let background_sprite_sheet_handle =
-            load_sprite_sheet(world, "./texture/BG.png", "./texture/BG.ron");
-        init_background_sprite(world, &background_sprite_sheet_handle);
-
-        let ground_sprite_sheet_handle =
-            load_sprite_sheet(world, "./texture/ground.png", "./texture/ground.ron");
-        init_ground_sprite(world, &ground_sprite_sheet_handle);
-
-        let crate_sprite_sheet_handle =
-            load_sprite_sheet(world, "./texture/Crate.png", "./texture/Crate.ron");
-        init_crate_sprite(world, &crate_sprite_sheet_handle, 0., GROUND_Y);
-        init_crate_sprite(world, &crate_sprite_sheet_handle, CRATE_SIZE, GROUND_Y);
-        init_crate_sprite(world, &crate_sprite_sheet_handle, 0., GROUND_Y + CRATE_SIZE);
-        let floating_crate_height = (PLAYER_H + 10) as f32 + GROUND_Y;
-        init_crate_sprite(world, &crate_sprite_sheet_handle, DISPLAY_WIDTH - CRATE_SIZE, floating_crate_height);
-        init_crate_sprite(world, &crate_sprite_sheet_handle, DISPLAY_WIDTH - 2. * CRATE_SIZE, floating_crate_height);
-        init_crate_sprite(world, &crate_sprite_sheet_handle, DISPLAY_WIDTH - 3. * CRATE_SIZE, floating_crate_height);
+        match &self.source {
+            LevelSource::Level(level) => {
+                let sprites = LevelSprites {
+                    background: load_sprite_sheet(world, "./texture/BG.png", "./texture/BG.ron"),
+                    ground: load_sprite_sheet(world, "./texture/ground.png", "./texture/ground.ron"),
+                    crate_: load_sprite_sheet(world, "./texture/Crate.png", "./texture/Crate.ron"),
+                };
+                init_level(world, level, &sprites);
+                self.level_sprites = Some(sprites);
+                self.level_handle = Some(load_ron_asset(world, LEVEL_PATH));
+            },
+            LevelSource::TiledMap(map) => init_tiled_map(world, map),
+        };
+        self.movement_config_handle = Some(load_ron_asset(world, MOVEMENT_CONFIG_PATH));
 
         world.register::<Player>();
+        world.register::<RigidBody>();
+        world.register::<Velocity>();
         let sprite_sheet_handle = load_player_sprite_sheet(world);
-        init_player(world, &sprite_sheet_handle);
+        let player = init_player(world, &sprite_sheet_handle, &self.source.player_spawn());
+
+        init_camera(world, player, &self.source.camera_settings());
+    }
+
+    fn update(&mut self, data: &mut StateData<'_, GameData<'_, '_>>) -> SimpleTrans {
+        self.reload_movement_config(data.world);
+        self.reload_level(data.world);
+
+        Trans::None
+    }
+}
+
+fn init_level(world: &mut World, level: &Level, sprites: &LevelSprites) {
+    let bodies = level_bodies(world, level);
+
+    init_background_sprite(world, &sprites.background, &level.background);
+
+    let platforms = level.ground.iter().chain(&level.one_way_platforms).cloned()
+        .chain(level.moving_platforms.iter().map(start_segment));
+    for (&entity, ground) in bodies.ground.iter().zip(platforms) {
+        add_ground_sprite(world, &sprites.ground, entity, &ground);
+    }
+
+    for (&entity, slope) in bodies.slopes.iter().zip(&level.slopes) {
+        add_slope_sprite(world, &sprites.ground, entity, slope);
+    }
+
+    for (&entity, crate_) in bodies.crates.iter().zip(&level.crates) {
+        add_crate_sprite(world, &sprites.crate_, entity, crate_);
+    }
+}
+
+fn delete_level_entities(world: &mut World) {
+    let level_entities: Vec<Entity> = (&world.entities(), &world.read_storage::<LevelEntity>()).join()
+        .map(|(entity, _)| entity)
+        .collect();
+    world.delete_entities(&level_entities).expect("Failed to delete level entities");
+}
+
+fn init_tiled_map(world: &mut World, map: &TiledMap) {
+    let sprite_sheet_handles: Vec<_> = map.tilesets.iter()
+        .map(|tileset| load_tileset_sprite_sheet(world, tileset))
+        .collect();
+
+    for tile in &map.tiles {
+        let tileset = &map.tilesets[tile.tileset];
+        init_tile_sprite(world, &sprite_sheet_handles[tile.tileset], tileset, tile);
+    }
 
-        init_camera(world);
+    // slopes and climbables are drawn by the map's own tiles
+    tiled_map_bodies(world, map);
+}
+
+/// Creates the collision bodies of a level. This is all the simulation needs, the renderer only adds
+/// sprites to them.
+pub fn init_level_bodies(world: &mut World, source: &LevelSource) {
+    match source {
+        LevelSource::Level(level) => {
+            level_bodies(world, level);
+        },
+        LevelSource::TiledMap(map) => tiled_map_bodies(world, map),
+    }
+}
+
+/// The bodies of a level which are drawn, in the order the level file lists them. Moving platforms are
+/// with the ground, after the one-way platforms.
+struct LevelBodies {
+    ground: Vec<Entity>,
+    slopes: Vec<Entity>,
+    crates: Vec<Entity>,
+}
+
+fn level_bodies(world: &mut World, level: &Level) -> LevelBodies {
+    // all of it is thrown away and rebuilt when the level file changes
+    world.register::<LevelEntity>();
+
+    let mut ground = vec![];
+    for segment in &level.ground {
+        ground.push(world.create_entity().with(ground_object(segment)).with(LevelEntity).build());
+    }
+    for platform in &level.one_way_platforms {
+        ground.push(world.create_entity().with(ground_object(platform)).with(OneWayPlatform).with(LevelEntity).build());
+    }
+    for segment in &level.moving_platforms {
+        ground.push(world.create_entity()
+            .with(ground_object(&start_segment(segment)))
+            .with(moving_platform(segment))
+            .with(LevelEntity)
+            .build());
+    }
+
+    let mut slopes = vec![];
+    for slope in &level.slopes {
+        let (two_dim_object, slope) = slope_object(slope);
+        slopes.push(world.create_entity().with(two_dim_object).with(slope).with(LevelEntity).build());
+    }
+
+    // there is no ladder art, so climbables aren't drawn
+    for climbable in &level.climbables {
+        world.create_entity().with(ground_object(climbable)).with(Climbable).with(LevelEntity).build();
+    }
+
+    let mut crates = vec![];
+    for crate_ in &level.crates {
+        let builder = world.create_entity().with(crate_object(crate_.left, crate_.bottom)).with(LevelEntity);
+        crates.push(if crate_.pushable {
+            builder.with(RigidBody::default()).with(Velocity::default()).with(Pushable).build()
+        } else {
+            builder.build()
+        });
+    }
+
+    LevelBodies { ground, slopes, crates }
+}
+
+fn tiled_map_bodies(world: &mut World, map: &TiledMap) {
+    for collider in &map.colliders {
+        init_collider(world, collider);
+    }
+
+    for slope in &map.slopes {
+        let (two_dim_object, slope) = slope_object(slope);
+        world.create_entity().with(two_dim_object).with(slope).build();
+    }
+
+    for climbable in &map.climbables {
+        world.create_entity().with(ground_object(climbable)).with(Climbable).build();
     }
 }
 
-fn init_camera(world: &mut World) {
+/// Creates the player's body. The renderer adds a sprite to it.
+pub fn init_player_body(world: &mut World, spawn: &Point) -> Entity {
     let mut transform = Transform::default();
-    transform.set_xyz(0.0, 0.0, 1.0);
+    let two_dim_object = player_object(spawn);
+    two_dim_object.update_transform_position(&mut transform);
 
     world
+        .create_entity()
+        .with(transform)
+        .with(two_dim_object)
+        .with(RigidBody::default())
+        .with(Velocity::default())
+        .with(Player::default())
+        .build()
+}
+
+fn player_object(spawn: &Point) -> TwoDimObject {
+    let mut two_dim_object = TwoDimObject::new(PLAYER_W as f32, PLAYER_H as f32);
+    two_dim_object.set_position(spawn.x, spawn.y);
+    two_dim_object
+}
+
+// objects are placed with `set_position`, which also sets where they were last step. otherwise a moving
+// one is drawn partway from the origin until its first physics step
+fn ground_object(ground: &GroundSegment) -> TwoDimObject {
+    let mut two_dim_object = TwoDimObject::new(ground.width, ground.height);
+    two_dim_object.set_position(ground.left + ground.width / 2., ground.top - ground.height / 2.);
+    two_dim_object
+}
+
+/// The moving platform as ground at its first waypoint.
+fn start_segment(segment: &MovingSegment) -> GroundSegment {
+    GroundSegment {
+        left: segment.waypoints[0].x,
+        top: segment.waypoints[0].y,
+        width: segment.width,
+        height: segment.height,
+    }
+}
+
+fn moving_platform(segment: &MovingSegment) -> MovingPlatform {
+    let waypoints = segment.waypoints.iter()
+        .map(|waypoint| TwoDimVector { x: waypoint.x, y: waypoint.y })
+        .collect();
+    MovingPlatform::new(waypoints, segment.mode, segment.speed, segment.wait_frames)
+}
+
+/// The box around the slope, along with the line across it.
+fn slope_object(slope: &SlopeSegment) -> (TwoDimObject, Slope) {
+    let height = slope.left_height.max(slope.right_height);
+    let mut two_dim_object = TwoDimObject::new(slope.width, height);
+    two_dim_object.set_position(slope.left + slope.width / 2., slope.bottom + height / 2.);
+    (two_dim_object, Slope { left_height: slope.left_height, right_height: slope.right_height })
+}
+
+fn crate_object(left: f32, bottom: f32) -> TwoDimObject {
+    let mut two_dim_object = TwoDimObject::new(CRATE_SIZE, CRATE_SIZE);
+    two_dim_object.set_position(left + CRATE_SIZE / 2., bottom + CRATE_SIZE / 2.);
+    two_dim_object
+}
+
+fn collider_object(collider: &Collider) -> TwoDimObject {
+    let mut two_dim_object = TwoDimObject::new(collider.width, collider.height);
+    two_dim_object.set_position(collider.left + collider.width / 2., collider.bottom + collider.height / 2.);
+    two_dim_object
+}
+
+fn init_camera(world: &mut World, player: Entity, settings: &CameraSettings) {
+    let mut transform = Transform::default();
+    transform.set_xyz(0.0, 0.0, 1.0);
+
+    // the projection is widened or heightened to match the window by CameraResizeSystem
+    let camera = world
         .create_entity()
         .with(Camera::from(Projection::orthographic(
             0.0,
-            DISPLAY_WIDTH, // todo set this by screen size?
+            VIRTUAL_WIDTH,
             0.0,
-            1000.,
+            VIRTUAL_HEIGHT,
         )))
+        .with(CameraFollow {
+            target: player,
+            view_size: TwoDimVector { x: VIRTUAL_WIDTH, y: VIRTUAL_HEIGHT },
+            dead_zone: TwoDimVector { x: settings.dead_zone.x, y: settings.dead_zone.y },
+            smoothing: settings.smoothing,
+        })
         .with(transform)
         .build();
+
+    init_letterbox_bars(world, camera);
 }
 
-fn init_player(world: &mut World, sprite_sheet_handle: &SpriteSheetHandle) -> Entity {
-    let scale = 1.;
+fn init_letterbox_bars(world: &mut World, camera: Entity) {
+    let texture_handle = {
+        let loader = world.read_resource::<Loader>();
+        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+        loader.load_from_data(TextureData::color([0., 0., 0., 1.]), (), &texture_storage)
+    };
 
-    let mut transform = Transform::default();
-    transform.set_scale(scale, scale, scale);
+    // a single pixel, which CameraResizeSystem scales to the size of each bar
+    let sprite_sheet = SpriteSheet {
+        texture: texture_handle,
+        sprites: vec![Sprite::from_pixel_values(1, 1, 1, 1, 0, 0, [0.; 2])],
+    };
+    let sprite_sheet_handle = {
+        let loader = world.read_resource::<Loader>();
+        loader.load_from_data(
+            sprite_sheet,
+            (),
+            &world.read_resource::<AssetStorage<SpriteSheet>>(),
+        )
+    };
+
+    for &edge in [BarEdge::Start, BarEdge::End].iter() {
+        let mut transform = Transform::default();
+        // just in front of the camera, so the bars cover everything else
+        transform.set_z(-0.5);
+        transform.set_scale(0., 0., 1.);
+
+        world.create_entity()
+            .with(transform)
+            .with(Parent { entity: camera })
+            .with(LetterboxBar { edge })
+            .with(SpriteRender {
+                sprite_sheet: sprite_sheet_handle.clone(),
+                sprite_number: 0,
+            })
+            .build();
+    }
+}
+
+fn init_player(world: &mut World, sprite_sheet_handle: &SpriteSheetHandle, spawn: &Point) -> Entity {
+    let player = init_player_body(world, spawn);
 
     let sprite_render = SpriteRender {
         sprite_sheet: sprite_sheet_handle.clone(),
         sprite_number: 60, // paddle is the first sprite in the sprite_sheet
     };
+    add_sprite(world, player, sprite_render);
 
-    let mut two_dim_object = TwoDimObject::new(PLAYER_W as f32, PLAYER_H as f32);
-    two_dim_object.set_position(500., 500.);
-    two_dim_object.update_transform_position(&mut transform);
-
-    world
-        .create_entity()
-        .with(transform)
-        .with(Player::new(two_dim_object))
-        .with(sprite_render)
-        .with(Transparent)
-        .build()
+    player
 }
 
-fn init_background_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle) -> Entity {
+fn init_background_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle, background: &Background) -> Entity {
     let mut transform = Transform::default();
-    transform.set_xyz(500., 500., -10.);
-    transform.set_scale(1., 1.5, 1.);
+    transform.set_xyz(background.position.x, background.position.y, -10.);
+    transform.set_scale(background.scale.x, background.scale.y, 1.);
     let sprite = SpriteRender {
         sprite_sheet: sprite_sheet.clone(),
         sprite_number: 0,
@@ -97,52 +441,111 @@ fn init_background_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle) -
         .with(transform)
         .with(sprite)
         .with(Transparent)
+        .with(LevelEntity)
         .build()
 }
 
-fn init_ground_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle) -> Entity {
+/// Draws a body with the sprite, on top of the transform it already has.
+fn add_sprite(world: &mut World, entity: Entity, sprite_render: SpriteRender) {
+    world.write_storage::<SpriteRender>().insert(entity, sprite_render)
+        .expect("Failed to add sprite");
+    world.write_storage::<Transparent>().insert(entity, Transparent)
+        .expect("Failed to add sprite");
+}
+
+fn add_transform(world: &mut World, entity: Entity, transform: Transform) {
+    world.write_storage::<Transform>().insert(entity, transform)
+        .expect("Failed to add transform");
+}
+
+fn add_ground_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle, entity: Entity, ground: &GroundSegment) {
     let mut transform = Transform::default();
     transform.set_z(-9.);
-    transform.set_scale(10., 1., 1.);
+    transform.set_scale(ground.width / GROUND_TILE_SIZE, ground.height / GROUND_TILE_SIZE, 1.);
+    ground_object(ground).update_transform_position(&mut transform);
+    add_transform(world, entity, transform);
+
     let sprite = SpriteRender {
         sprite_sheet: sprite_sheet.clone(),
         sprite_number: 0,
     };
+    add_sprite(world, entity, sprite);
+}
 
-    let mut two_dim_object = TwoDimObject::new(1280., 128.);
-    two_dim_object.set_left(0.);
-    two_dim_object.set_top(GROUND_Y);
-    two_dim_object.update_transform_position(&mut transform);
+/// There is no art for slopes, so the ground sprite is stretched into a strip along the top of it.
+fn add_slope_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle, entity: Entity, slope: &SlopeSegment) {
+    let rise = slope.right_height - slope.left_height;
+    let angle = rise.atan2(slope.width);
+    let length = (slope.width * slope.width + rise * rise).sqrt();
 
-    world.create_entity()
-        .with(transform)
-        .with(two_dim_object)
-        .with(sprite)
-        .with(Transparent)
-        .build()
+    let mut transform = Transform::default();
+    // the strip hangs below the line, so its top edge is where bodies stand
+    transform.set_xyz(
+        slope.left + slope.width / 2. + angle.sin() * SLOPE_SPRITE_THICKNESS / 2.,
+        slope.bottom + (slope.left_height + slope.right_height) / 2. - angle.cos() * SLOPE_SPRITE_THICKNESS / 2.,
+        -9.,
+    );
+    transform.set_rotation_euler(0., 0., angle);
+    transform.set_scale(length / GROUND_TILE_SIZE, SLOPE_SPRITE_THICKNESS / GROUND_TILE_SIZE, 1.);
+    add_transform(world, entity, transform);
+
+    let sprite = SpriteRender {
+        sprite_sheet: sprite_sheet.clone(),
+        sprite_number: 0,
+    };
+    add_sprite(world, entity, sprite);
 }
 
-fn init_crate_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle, left: f32, bottom: f32) -> Entity {
+fn add_crate_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle, entity: Entity, crate_: &Crate) {
     let mut transform = Transform::default();
     transform.set_z(-9.);
+    crate_object(crate_.left, crate_.bottom).update_transform_position(&mut transform);
+    add_transform(world, entity, transform);
+
     let sprite = SpriteRender {
         sprite_sheet: sprite_sheet.clone(),
         sprite_number: 0,
     };
+    add_sprite(world, entity, sprite);
+}
 
-    let mut two_dim_object = TwoDimObject::new(CRATE_SIZE, CRATE_SIZE);
-    two_dim_object.set_left(left);
-    two_dim_object.set_bottom(bottom);
-    two_dim_object.update_transform_position(&mut transform);
+fn init_tile_sprite(world: &mut World, sprite_sheet: &SpriteSheetHandle, tileset: &Tileset, tile: &Tile) -> Entity {
+    let mut transform = Transform::default();
+    transform.set_xyz(
+        tile.left + tileset.tile_w as f32 / 2.,
+        tile.bottom + tileset.tile_h as f32 / 2.,
+        -9.,
+    );
+    let sprite = SpriteRender {
+        sprite_sheet: sprite_sheet.clone(),
+        sprite_number: tile.sprite_number,
+    };
 
     world.create_entity()
         .with(transform)
-        .with(two_dim_object)
         .with(sprite)
         .with(Transparent)
         .build()
 }
 
+fn init_collider(world: &mut World, collider: &Collider) -> Entity {
+    let builder = world.create_entity().with(collider_object(collider));
+    if collider.one_way {
+        builder.with(OneWayPlatform).build()
+    } else {
+        builder.build()
+    }
+}
+
+fn load_ron_asset<A>(world: &mut World, path: &str) -> Handle<A>
+where
+    A: Asset,
+    A::Data: for<'a> serde::Deserialize<'a> + Send + Sync + 'static,
+{
+    let loader = world.read_resource::<Loader>();
+    loader.load(path, RonFormat, (), (), &world.read_resource::<AssetStorage<A>>())
+}
+
 fn load_sprite_sheet(world: &mut World, png_path: &str, ron_path: &str) -> SpriteSheetHandle {
     let texture_handle = {
         let loader = world.read_resource::<Loader>();
@@ -208,4 +611,39 @@ fn load_player_sprite_sheet(world: &mut World) -> SpriteSheetHandle {
         (),
         &world.read_resource::<AssetStorage<SpriteSheet>>(),
     )
-}
\ No newline at end of file
+}
+fn load_tileset_sprite_sheet(world: &mut World, tileset: &Tileset) -> SpriteSheetHandle {
+    let texture_handle = {
+        let loader = world.read_resource::<Loader>();
+        let texture_storage = world.read_resource::<AssetStorage<Texture>>();
+        loader.load(
+            tileset.image_path.as_str(),
+            PngFormat,
+            TextureMetadata::srgb_scale(),
+            (),
+            &texture_storage,
+        )
+    };
+
+    let loader = world.read_resource::<Loader>();
+
+    let sprites = (0..tileset.tile_count as usize)
+        .map(|sprite_number| {
+            let (offset_x, offset_y) = tileset.tile_offset(sprite_number);
+            Sprite::from_pixel_values(
+                tileset.image_w, tileset.image_h, tileset.tile_w, tileset.tile_h, offset_x, offset_y, [0.; 2],
+            )
+        })
+        .collect();
+
+    let sprite_sheet = SpriteSheet {
+        texture: texture_handle,
+        sprites,
+    };
+
+    loader.load_from_data(
+        sprite_sheet,
+        (),
+        &world.read_resource::<AssetStorage<SpriteSheet>>(),
+    )
+}
@@ -0,0 +1,119 @@
+//! Runs the game loop without a window or GPU, driving the player from an `InputScript`.
+
+use amethyst::{
+    core::timing::Time,
+    ecs::{Join},
+    prelude::*,
+};
+use crate::{
+    PHYSICS_STEP_SECONDS, PlatformerBundle,
+    components::{Player, PlayerState, TwoDimObject, Velocity},
+    config::MovementConfig,
+    entities::{LevelSource, init_level_bodies, init_player_body},
+    systems::{InputScript},
+};
+
+/// The game's systems without `RenderBundle`, with scripted input in place of `InputHandler`.
+pub fn headless_game_data<'a, 'b>(script: InputScript) -> amethyst::Result<GameDataBuilder<'a, 'b>> {
+    GameDataBuilder::default()
+        .with_bundle(PlatformerBundle::new().with_input_script(script))
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BodySnapshot {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+    pub velocity_x: f32,
+    pub velocity_y: f32,
+}
+
+impl BodySnapshot {
+    fn new(two_dim: &TwoDimObject, velocity: Option<&Velocity>) -> Self {
+        let velocity = velocity.cloned().unwrap_or_default();
+        BodySnapshot {
+            left: two_dim.left(),
+            right: two_dim.right(),
+            bottom: two_dim.bottom(),
+            top: two_dim.top(),
+            velocity_x: velocity.x,
+            velocity_y: velocity.y,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct PlayerSnapshot {
+    pub body: BodySnapshot,
+    pub state: PlayerState,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Snapshot {
+    pub frame: usize,
+    pub players: Vec<PlayerSnapshot>,
+    pub objects: Vec<BodySnapshot>,
+}
+
+/// Steps the game one fixed physics step per frame, so results don't depend on the host machine.
+pub struct Simulation {
+    world: World,
+    game_data: GameData<'static, 'static>,
+    frame: usize,
+}
+
+impl Simulation {
+    pub fn new(source: &LevelSource, movement: MovementConfig, script: InputScript) -> Self {
+        let mut world = World::new();
+        world.add_resource(Time::default());
+        world.add_resource(movement);
+        world.add_resource(source.bounds());
+        let game_data = headless_game_data(script)
+            .expect("Failed to add platformer systems")
+            .build(&mut world);
+
+        init_level_bodies(&mut world, source);
+        init_player_body(&mut world, &source.player_spawn());
+
+        Simulation {
+            world,
+            game_data,
+            frame: 0,
+        }
+    }
+
+    pub fn step(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.world.write_resource::<Time>().set_delta_seconds(PHYSICS_STEP_SECONDS);
+            self.game_data.update(&self.world);
+            self.world.maintain();
+            self.frame += 1;
+        }
+    }
+
+    pub fn world(&self) -> &World {
+        &self.world
+    }
+
+    /// For adding entities the level file can't describe, such as extra rigid bodies.
+    pub fn world_mut(&mut self) -> &mut World {
+        &mut self.world
+    }
+
+    pub fn snapshot(&self) -> Snapshot {
+        let players = self.world.read_storage::<Player>();
+        let two_dim_objects = self.world.read_storage::<TwoDimObject>();
+        let velocities = self.world.read_storage::<Velocity>();
+
+        Snapshot {
+            frame: self.frame,
+            players: (&players, &two_dim_objects, (&velocities).maybe()).join()
+                .map(|(player, two_dim, velocity)| PlayerSnapshot { body: BodySnapshot::new(two_dim, velocity), state: player.state })
+                .collect(),
+            objects: (&two_dim_objects, (&velocities).maybe(), !&players).join()
+                .map(|(two_dim, velocity, _)| BodySnapshot::new(two_dim, velocity))
+                .collect(),
+        }
+    }
+}
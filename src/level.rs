@@ -0,0 +1,303 @@
+use std::{error, fmt};
+
+use amethyst::{
+    assets::{self, Asset, Handle, ProcessingState},
+    config::{Config, ConfigError},
+    ecs::VecStorage,
+};
+use serde_derive::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Background {
+    pub position: Point,
+    pub scale: Point,
+}
+
+impl Default for Background {
+    fn default() -> Self {
+        Background {
+            position: Point { x: 500., y: 500. },
+            scale: Point { x: 1., y: 1.5 },
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct GroundSegment {
+    pub left: f32,
+    pub top: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+/// Ground whose top is a straight line, from `left_height` above the bottom left corner to
+/// `right_height` above the bottom right corner. A 45° ramp is as wide as it is high, and a 22.5° one
+/// about two and a half times as wide.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct SlopeSegment {
+    pub left: f32,
+    pub bottom: f32,
+    pub width: f32,
+    pub left_height: f32,
+    pub right_height: f32,
+}
+
+/// What a moving platform does after reaching its last waypoint.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+pub enum PathMode {
+    /// Heads straight back to the first waypoint and goes round again.
+    Loop,
+    /// Retraces its path back to the first waypoint.
+    #[default]
+    PingPong,
+}
+
+/// A platform which moves between waypoints, carrying whatever stands on it.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct MovingSegment {
+    pub width: f32,
+    pub height: f32,
+    /// Positions of the platform's top left corner, the platform starts at the first one.
+    pub waypoints: Vec<Point>,
+    #[serde(default)]
+    pub mode: PathMode,
+    /// In units per second.
+    pub speed: f32,
+    /// Physics steps to wait at each waypoint.
+    #[serde(default)]
+    pub wait_frames: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Crate {
+    pub left: f32,
+    pub bottom: f32,
+    /// Whether the player can push the crate around, in which case it also falls under gravity.
+    #[serde(default)]
+    pub pushable: bool,
+}
+
+/// Edges of the level, which the player can't walk past and the camera won't show beyond.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct Bounds {
+    pub left: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub top: f32,
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Bounds {
+            left: f32::NEG_INFINITY,
+            right: f32::INFINITY,
+            bottom: f32::NEG_INFINITY,
+            top: f32::INFINITY,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct CameraSettings {
+    /// Half the width and height of the area around the center of the view the player can move
+    /// in without the camera following.
+    pub dead_zone: Point,
+    /// How quickly the camera catches up with the player, higher is faster.
+    pub smoothing: f32,
+}
+
+impl Default for CameraSettings {
+    fn default() -> Self {
+        CameraSettings {
+            dead_zone: Point { x: 100., y: 150. },
+            smoothing: 5.,
+        }
+    }
+}
+
+/// Layout of a single level, loaded from a RON file in `resources/`.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+pub struct Level {
+    pub bounds: Bounds,
+    #[serde(default)]
+    pub camera: CameraSettings,
+    pub background: Background,
+    pub ground: Vec<GroundSegment>,
+    /// Ground which can be jumped up through and dropped down through.
+    #[serde(default)]
+    pub one_way_platforms: Vec<GroundSegment>,
+    #[serde(default)]
+    pub moving_platforms: Vec<MovingSegment>,
+    #[serde(default)]
+    pub slopes: Vec<SlopeSegment>,
+    /// Ladders and other areas the player can climb around in.
+    #[serde(default)]
+    pub climbables: Vec<GroundSegment>,
+    pub crates: Vec<Crate>,
+    pub player_spawn: Point,
+}
+
+/// Lets `InitialState` watch the level file and rebuild the level when it is saved.
+impl Asset for Level {
+    const NAME: &'static str = "platformer::Level";
+    type Data = Self;
+    type HandleStorage = VecStorage<Handle<Self>>;
+}
+
+impl From<Level> for assets::Result<ProcessingState<Level>> {
+    fn from(level: Level) -> Self {
+        level.validate().map_err(|e| e.to_string())?;
+        Ok(ProcessingState::Loaded(level))
+    }
+}
+
+#[derive(Debug)]
+pub enum LevelError {
+    Load(ConfigError),
+    InvalidBounds,
+    InvalidCamera,
+    NoGround,
+    InvalidGround { index: usize, reason: &'static str },
+    InvalidOneWayPlatform { index: usize, reason: &'static str },
+    InvalidMovingPlatform { index: usize, reason: &'static str },
+    InvalidSlope { index: usize, reason: &'static str },
+    InvalidClimbable { index: usize, reason: &'static str },
+    InvalidCrate { index: usize, reason: &'static str },
+    InvalidSpawn,
+}
+
+impl fmt::Display for LevelError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LevelError::Load(e) => write!(f, "failed to load level file: {}", e),
+            LevelError::InvalidBounds =>
+                write!(f, "level bounds must be finite, with left below right and bottom below top"),
+            LevelError::InvalidCamera =>
+                write!(f, "camera dead zone must not be negative, and smoothing must be positive"),
+            LevelError::NoGround => write!(f, "level must contain at least one ground segment"),
+            LevelError::InvalidGround { index, reason } => write!(f, "ground segment {} is invalid: {}", index, reason),
+            LevelError::InvalidOneWayPlatform { index, reason } =>
+                write!(f, "one-way platform {} is invalid: {}", index, reason),
+            LevelError::InvalidMovingPlatform { index, reason } =>
+                write!(f, "moving platform {} is invalid: {}", index, reason),
+            LevelError::InvalidSlope { index, reason } => write!(f, "slope {} is invalid: {}", index, reason),
+            LevelError::InvalidClimbable { index, reason } => write!(f, "climbable {} is invalid: {}", index, reason),
+            LevelError::InvalidCrate { index, reason } => write!(f, "crate {} is invalid: {}", index, reason),
+            LevelError::InvalidSpawn => write!(f, "player spawn must be a finite position"),
+        }
+    }
+}
+
+impl error::Error for LevelError {}
+
+impl From<ConfigError> for LevelError {
+    fn from(e: ConfigError) -> Self {
+        LevelError::Load(e)
+    }
+}
+
+impl Level {
+    pub fn load(path: &str) -> Result<Self, LevelError> {
+        let level = Level::load_no_fallback(path)?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    pub fn validate(&self) -> Result<(), LevelError> {
+        let bounds = &self.bounds;
+        let bounds_finite = bounds.left.is_finite() && bounds.right.is_finite()
+            && bounds.bottom.is_finite() && bounds.top.is_finite();
+        if !(bounds_finite && bounds.left < bounds.right && bounds.bottom < bounds.top) {
+            return Err(LevelError::InvalidBounds);
+        }
+
+        let camera = &self.camera;
+        if !(camera.dead_zone.x >= 0. && camera.dead_zone.y >= 0. && camera.smoothing > 0.) {
+            return Err(LevelError::InvalidCamera);
+        }
+
+        if self.ground.is_empty() {
+            return Err(LevelError::NoGround);
+        }
+
+        for (index, ground) in self.ground.iter().enumerate() {
+            validate_segment(ground).map_err(|reason| LevelError::InvalidGround { index, reason })?;
+        }
+
+        for (index, platform) in self.one_way_platforms.iter().enumerate() {
+            validate_segment(platform).map_err(|reason| LevelError::InvalidOneWayPlatform { index, reason })?;
+        }
+
+        for (index, platform) in self.moving_platforms.iter().enumerate() {
+            validate_moving_segment(platform).map_err(|reason| LevelError::InvalidMovingPlatform { index, reason })?;
+        }
+
+        for (index, slope) in self.slopes.iter().enumerate() {
+            validate_slope(slope).map_err(|reason| LevelError::InvalidSlope { index, reason })?;
+        }
+
+        for (index, climbable) in self.climbables.iter().enumerate() {
+            validate_segment(climbable).map_err(|reason| LevelError::InvalidClimbable { index, reason })?;
+        }
+
+        for (index, crate_) in self.crates.iter().enumerate() {
+            if !(crate_.left.is_finite() && crate_.bottom.is_finite()) {
+                return Err(LevelError::InvalidCrate { index, reason: "position must be finite" });
+            }
+        }
+
+        if !(self.player_spawn.x.is_finite() && self.player_spawn.y.is_finite()) {
+            return Err(LevelError::InvalidSpawn);
+        }
+
+        Ok(())
+    }
+}
+
+fn validate_segment(segment: &GroundSegment) -> Result<(), &'static str> {
+    if !(segment.left.is_finite() && segment.top.is_finite()) {
+        return Err("position must be finite");
+    }
+    if !(segment.width > 0. && segment.height > 0.) {
+        return Err("width and height must be positive");
+    }
+    Ok(())
+}
+
+fn validate_moving_segment(segment: &MovingSegment) -> Result<(), &'static str> {
+    if !(segment.width > 0. && segment.height > 0.) {
+        return Err("width and height must be positive");
+    }
+    if segment.waypoints.len() < 2 {
+        return Err("must have at least two waypoints");
+    }
+    if !segment.waypoints.iter().all(|waypoint| waypoint.x.is_finite() && waypoint.y.is_finite()) {
+        return Err("waypoints must be finite");
+    }
+    if !(segment.speed > 0. && segment.speed.is_finite()) {
+        return Err("speed must be positive");
+    }
+    Ok(())
+}
+
+fn validate_slope(slope: &SlopeSegment) -> Result<(), &'static str> {
+    if !(slope.left.is_finite() && slope.bottom.is_finite()) {
+        return Err("position must be finite");
+    }
+    if !(slope.width > 0. && slope.width.is_finite()) {
+        return Err("width must be positive");
+    }
+    if !(slope.left_height >= 0. && slope.right_height >= 0. && slope.left_height.is_finite() && slope.right_height.is_finite()) {
+        return Err("heights must not be negative");
+    }
+    if slope.left_height == slope.right_height {
+        return Err("left and right heights must differ, flat ground belongs in `ground`");
+    }
+    Ok(())
+}
\ No newline at end of file
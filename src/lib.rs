@@ -0,0 +1,25 @@
+pub mod bundle;
+pub mod collision;
+pub mod components;
+pub mod config;
+pub mod entities;
+pub mod headless;
+pub mod level;
+pub mod systems;
+pub mod tiled;
+
+pub use bundle::PlatformerBundle;
+
+pub const PLAYER_W: u32 = 90;
+pub const TOTAL_PLAYER_SPRITE_HEIGHT: u32 = 184;
+pub const PLAYER_SPRITE_Y_PADDING: u32 = 20; // pixels between sprites
+pub const PLAYER_H: u32 = TOTAL_PLAYER_SPRITE_HEIGHT - PLAYER_SPRITE_Y_PADDING;
+pub const GROUND_TILE_SIZE: f32 = 128.;
+pub const CRATE_SIZE: f32 = 77.;
+// size of the area the camera shows, in world units, whatever the size of the window
+pub const VIRTUAL_WIDTH: f32 = 1000.;
+pub const VIRTUAL_HEIGHT: f32 = 1000.;
+pub const LEVEL_PATH: &str = "resources/level.ron";
+pub const MOVEMENT_CONFIG_PATH: &str = "resources/movement_config.ron";
+pub const PHYSICS_STEP_SECONDS: f32 = 1. / 60.;
+pub const MAX_PHYSICS_STEPS_PER_FRAME: u32 = 5;
\ No newline at end of file
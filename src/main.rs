@@ -1,27 +1,67 @@
 use amethyst::{
+    assets::{HotReloadBundle, Processor},
     core::{TransformBundle},
     input::{InputBundle},
     prelude::*,
     renderer::{ALPHA, ColorMask, DisplayConfig, DrawFlat2D, Pipeline, RenderBundle, Stage},
 };
 
-mod entities;
-use entities::{InitialState};
-mod components;
-mod systems;
-use systems::{ControlSystem, PhysicsSystem, AnimationSystem};
-
-pub const PLAYER_W: u32 = 90;
-pub const TOTAL_PLAYER_SPRITE_HEIGHT: u32 = 184;
-pub const PLAYER_SPRITE_Y_PADDING: u32 = 20; // pixels between sprites
-pub const PLAYER_H: u32 = TOTAL_PLAYER_SPRITE_HEIGHT - PLAYER_SPRITE_Y_PADDING;
-pub const GROUND_Y: f32 = 74.;
-pub const CRATE_SIZE: f32 = 77.;
-pub const DISPLAY_WIDTH: f32 = 1000.;
-pub const PLAYER_MAX_X_VELOCITY: f32 = 5.;
+use amethyst_2d_platformer_demo::{
+    LEVEL_PATH, MOVEMENT_CONFIG_PATH, PlatformerBundle,
+    config::{MovementConfig},
+    entities::{InitialState, LevelSource},
+    headless::{Simulation},
+    level::{Level},
+    systems::{CameraResizeSystem, InputScript},
+    tiled::{TiledMap},
+};
+
+fn load_level_source(map_path: Option<String>) -> LevelSource {
+    // a Tiled map, as TMX or JSON, can be passed instead of the default level
+    match map_path {
+        Some(map_path) => LevelSource::TiledMap(TiledMap::load(&map_path)
+            .unwrap_or_else(|e| panic!("Failed to load {}: {}", map_path, e))),
+        None => LevelSource::Level(Level::load(LEVEL_PATH)
+            .unwrap_or_else(|e| panic!("Failed to load {}: {}", LEVEL_PATH, e))),
+    }
+}
+
+fn load_movement_config() -> MovementConfig {
+    MovementConfig::load(MOVEMENT_CONFIG_PATH)
+        .unwrap_or_else(|e| panic!("Failed to load {}: {}", MOVEMENT_CONFIG_PATH, e))
+}
+
+fn run_headless(script_path: &str, source: &LevelSource) {
+    let script = InputScript::load_no_fallback(script_path)
+        .unwrap_or_else(|e| panic!("Failed to load {}: {}", script_path, e));
+    let frames = script.frames();
+
+    let mut simulation = Simulation::new(source, load_movement_config(), script);
+    simulation.step(frames);
+    println!("{:#?}", simulation.snapshot());
+}
 
 fn main() -> amethyst::Result<()> {
     amethyst::start_logger(Default::default());
+
+    // usage: [--headless <input script>] [tiled map]
+    let mut args = std::env::args().skip(1);
+    let mut headless_script = None;
+    let mut map_path = None;
+    while let Some(arg) = args.next() {
+        if arg == "--headless" {
+            headless_script = Some(args.next().expect("--headless requires an input script"));
+        } else {
+            map_path = Some(arg);
+        }
+    }
+    let level_source = load_level_source(map_path);
+
+    if let Some(script_path) = headless_script {
+        run_headless(&script_path, &level_source);
+        return Ok(());
+    }
+
     let config = DisplayConfig::load("./resources/display_config.ron");
     let pipe = Pipeline::build().with_stage(
         Stage::with_backbuffer()
@@ -41,12 +81,17 @@ fn main() -> amethyst::Result<()> {
             .with_sprite_sheet_processor()
             .with_sprite_visibility_sorting(&[])
         )?
-        .with(ControlSystem, "control_system", &[])
-        .with(PhysicsSystem, "physics_system", &["control_system"])
-        .with(AnimationSystem, "animation_system", &["physics_system"]);
+        // lets resources/*.ron be edited while the game is running
+        .with_bundle(HotReloadBundle::default())?
+        .with(Processor::<Level>::new(), "level_processor", &[])
+        .with(Processor::<MovementConfig>::new(), "movement_config_processor", &[])
+        .with_bundle(PlatformerBundle::new())?
+        .with(CameraResizeSystem::default(), "camera_resize_system", &[]);
 
     let mut game =
-        Application::build("./", InitialState)?.build(game_data)?;
+        Application::build("./", InitialState::new(level_source))?
+            .with_resource(load_movement_config())
+            .build(game_data)?;
     game.run();
 
     Ok(())
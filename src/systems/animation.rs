@@ -1,13 +1,23 @@
 use amethyst::{
-    core::{Transform},
-    ecs::{Entities, Join, System, WriteStorage},
+    core::Transform,
+    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
     renderer::{Flipped, SpriteRender},
 };
 use crate::{
-    PLAYER_MAX_X_VELOCITY,
-    components::{Player, PlayerState}
+    components::{Player, PlayerState, RigidBody, TwoDimObject, Velocity},
+    config::MovementConfig,
+    systems::FixedTimestep,
+    PLAYER_H,
 };
 
+/// Vertical speed either side of zero, in units per second, which counts as the top of a jump.
+const APEX_VELOCITY: f32 = 120.;
+/// Physics steps the landing pose is held for.
+const LANDING_STEPS: usize = 6;
+/// Physics steps the player can be off the ground without leaving a grounded state, so losing contact
+/// for a moment, such as walking down a slope, doesn't flicker into falling.
+const GROUND_GRACE_STEPS: u32 = 2;
+
 pub struct AnimationSystem;
 
 impl<'s> System<'s> for AnimationSystem {
@@ -17,15 +27,21 @@ impl<'s> System<'s> for AnimationSystem {
         WriteStorage<'s, SpriteRender>,
         WriteStorage<'s, Flipped>,
         WriteStorage<'s, Transform>,
+        ReadStorage<'s, Velocity>,
+        ReadStorage<'s, RigidBody>,
+        ReadStorage<'s, TwoDimObject>,
+        Read<'s, FixedTimestep>,
+        Read<'s, MovementConfig>,
     );
 
-    fn run(&mut self, (entities, mut players, mut sprites, mut flipped, mut transforms): Self::SystemData) {
-        for (player_entity, mut player, mut sprite, mut transform) in (&entities, &mut players, &mut sprites, &mut transforms).join() {
+    fn run(&mut self, (entities, mut players, mut sprites, mut flipped, mut transforms, velocities, rigid_bodies, two_dim_objects, timestep, movement): Self::SystemData) {
+        // sprites are optional so the player state is still tracked when running without a renderer
+        for (player_entity, player, sprite, transform, velocity, rigid_body, two_dim_object) in (&entities, &mut players, (&mut sprites).maybe(), (&mut transforms).maybe(), &velocities, &rigid_bodies, &two_dim_objects).join() {
             // set sprite direction
-            if player.two_dim.velocity.x > 0. {
+            if velocity.x > 0. {
                 // face right
                 flipped.remove(player_entity);
-            } else if player.two_dim.velocity.x < 0. {
+            } else if velocity.x < 0. {
                 // face left
                 flipped.insert(player_entity, Flipped::Horizontal)
                     .expect("Failed to flip");
@@ -33,28 +49,79 @@ impl<'s> System<'s> for AnimationSystem {
 
             // set player state
             let current_state = player.state;
+            let contacts = two_dim_object.contacts;
+            let in_air = !contacts.on_ground() && (velocity.y > 0. || player.steps_since_grounded > GROUND_GRACE_STEPS);
             let next_state =
-                if player.two_dim.velocity.y != 0. { PlayerState::Jumping }
-                else if player.two_dim.velocity.x.abs() > PLAYER_MAX_X_VELOCITY * 0.7 { PlayerState::Running }
-                else if player.two_dim.velocity.x != 0. { PlayerState::Walking }
+                if rigid_body.climbing { PlayerState::Climbing }
+                else if player.dash_steps_left > 0 { PlayerState::Dashing }
+                else if player.crouching && contacts.on_ground() { PlayerState::Crouching }
+                else if contacts.pushing_into_wall(velocity.x) && in_air && velocity.y < 0. { PlayerState::WallSlide }
+                else if player.air_jump_started || (current_state == PlayerState::AirJumping && in_air && velocity.y > APEX_VELOCITY) { PlayerState::AirJumping }
+                else if in_air { airborne_state(current_state, velocity.y) }
+                else if current_state.is_airborne() || (current_state == PlayerState::Landing && player.ticks < LANDING_STEPS) { PlayerState::Landing }
+                else if velocity.x.abs() > movement.max_x_velocity * 0.7 { PlayerState::Running }
+                else if velocity.x != 0. { PlayerState::Walking }
                 else { PlayerState::Idle };
 
-            if current_state != next_state {
+            if current_state != next_state || player.air_jump_started {
                 player.state = next_state;
-                player.ticks = 0; // reset animation if player state changed
+                player.ticks = 0; // reset animation if player state changed, or on every new air jump
             }
+            player.air_jump_started = false;
 
             let (sprite_initial_index, num_sprites) = match player.state {
                 PlayerState::Idle => (15, 15),
                 PlayerState::Walking => (60, 15),
                 PlayerState::Running => (45, 15),
-                PlayerState::Jumping => (35, 7),
+                // the jump is split up into its rising, top and falling frames
+                PlayerState::Rising => (36, 2),
+                PlayerState::Apex => (38, 1),
+                PlayerState::Falling => (39, 3),
+                // the crouched take-off frame of the jump, doubling as the squash on landing
+                PlayerState::Landing => (35, 1),
+                // there is no wall slide art, so hold one of the falling frames of the jump
+                PlayerState::WallSlide => (40, 1),
+                // the rise replayed from the take-off frame, so each air jump is visible
+                PlayerState::AirJumping => (35, 3),
+                // the walk cycle, which only plays while the player is moving
+                PlayerState::Climbing => (60, 15),
+                // the crouched take-off frame of the jump, held
+                PlayerState::Crouching => (35, 1),
+                // a stride of the run, held for the length of the dash
+                PlayerState::Dashing => (50, 1),
             };
-            let game_frames_per_animation_frame = 6;
-            sprite.sprite_number = (player.ticks / game_frames_per_animation_frame) % num_sprites + sprite_initial_index;
-            player.ticks = player.ticks.wrapping_add(1);
+            // the sprite is squashed to fit the body, which is shorter while crouching
+            if let Some(transform) = transform {
+                transform.set_scale(1., two_dim_object.size.y / PLAYER_H as f32, 1.);
+            }
 
-            player.two_dim.update_transform_position(&mut transform);
+            let physics_steps_per_animation_frame = 6;
+            if let Some(sprite) = sprite {
+                sprite.sprite_number = (player.ticks / physics_steps_per_animation_frame) % num_sprites + sprite_initial_index;
+            }
+            // holding still while climbing freezes the animation
+            let climbing_still = player.state == PlayerState::Climbing && velocity.x == 0. && velocity.y == 0.;
+            if !climbing_still {
+                player.ticks = player.ticks.wrapping_add(timestep.steps() as usize);
+            }
         }
     }
+}
+
+/// Which part of a jump or fall the player is in. Past the top they keep falling unless something
+/// sends them back up faster than `APEX_VELOCITY`, so a blip in velocity around the top doesn't
+/// restart the rise.
+fn airborne_state(current_state: PlayerState, velocity_y: f32) -> PlayerState {
+    if velocity_y > APEX_VELOCITY {
+        PlayerState::Rising
+    } else if velocity_y < -APEX_VELOCITY || current_state == PlayerState::Falling {
+        PlayerState::Falling
+    } else if current_state.is_airborne() {
+        PlayerState::Apex
+    } else if velocity_y > 0. {
+        PlayerState::Rising
+    } else {
+        // walked off a ledge
+        PlayerState::Falling
+    }
 }
\ No newline at end of file
@@ -0,0 +1,137 @@
+use std::collections::HashMap;
+
+use amethyst::{
+    ecs::{Entities, Entity, ReadStorage, ReaderId, Resources, System, SystemData, Write, WriteStorage, storage::ComponentEvent, world::Index},
+};
+use crate::{
+    GROUND_TILE_SIZE,
+    components::TwoDimObject,
+};
+
+/// Cells of the grid an object covers, inclusive on both ends.
+#[derive(Clone, Copy, PartialEq, Debug)]
+struct CellRange {
+    min_x: i32,
+    min_y: i32,
+    max_x: i32,
+    max_y: i32,
+}
+
+/// Uniform grid of every `TwoDimObject`, so collision checks only look at objects near a body
+/// instead of the whole level.
+pub struct SpatialHash {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<Entity>>,
+    // by id, as that's all the storage says about a deleted entity
+    entries: HashMap<Index, (Entity, CellRange)>,
+}
+
+impl Default for SpatialHash {
+    fn default() -> Self {
+        SpatialHash::new(GROUND_TILE_SIZE)
+    }
+}
+
+impl SpatialHash {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialHash {
+            cell_size,
+            cells: HashMap::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Adds the object, or moves it if it is already in the grid.
+    pub fn insert(&mut self, entity: Entity, object: &TwoDimObject) {
+        let range = self.cell_range(object.left(), object.bottom(), object.right(), object.top());
+        match self.entries.get(&entity.id()) {
+            Some(&(old_entity, old_range)) if old_entity == entity && old_range == range => return,
+            Some(_) => self.remove(entity.id()),
+            None => {},
+        }
+
+        for cell in cells(range) {
+            self.cells.entry(cell).or_insert_with(Vec::new).push(entity);
+        }
+        self.entries.insert(entity.id(), (entity, range));
+    }
+
+    /// Removes whichever entity with the id is in the grid.
+    fn remove(&mut self, id: Index) {
+        let (entity, range) = match self.entries.remove(&id) {
+            Some(entry) => entry,
+            None => return,
+        };
+
+        for cell in cells(range) {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|&other| other != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        }
+    }
+
+    /// Entities in the grid cells the area touches, in entity order. These may not overlap the area
+    /// themselves, it only rules out everything further away.
+    pub fn query(&self, left: f32, bottom: f32, right: f32, top: f32) -> Vec<Entity> {
+        let mut found: Vec<Entity> = cells(self.cell_range(left, bottom, right, top))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flat_map(|entities| entities.iter().cloned())
+            .collect();
+        found.sort_unstable_by_key(|entity| entity.id());
+        found.dedup();
+        found
+    }
+
+    fn cell_range(&self, left: f32, bottom: f32, right: f32, top: f32) -> CellRange {
+        CellRange {
+            min_x: (left / self.cell_size).floor() as i32,
+            min_y: (bottom / self.cell_size).floor() as i32,
+            max_x: (right / self.cell_size).floor() as i32,
+            max_y: (top / self.cell_size).floor() as i32,
+        }
+    }
+}
+
+fn cells(range: CellRange) -> impl Iterator<Item = (i32, i32)> {
+    (range.min_x..=range.max_x).flat_map(move |x| (range.min_y..=range.max_y).map(move |y| (x, y)))
+}
+
+/// Keeps `SpatialHash` in step with the world, picking up objects which were created, deleted or
+/// changed since the last frame. Objects which stay put, which is most of a level, cost nothing once
+/// they are in the grid.
+#[derive(Default)]
+pub struct SpatialHashSystem {
+    reader: Option<ReaderId<ComponentEvent>>,
+}
+
+impl<'s> System<'s> for SpatialHashSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, TwoDimObject>,
+        Write<'s, SpatialHash>,
+    );
+
+    fn run(&mut self, (entities, two_dim_objects, mut spatial_hash): Self::SystemData) {
+        let reader = self.reader.as_mut().expect("SpatialHashSystem was not set up");
+        for event in two_dim_objects.channel().read(reader) {
+            match *event {
+                ComponentEvent::Inserted(id) | ComponentEvent::Modified(id) => {
+                    // the object may have been removed again since
+                    let entity = entities.entity(id);
+                    if let Some(two_dim_object) = two_dim_objects.get(entity) {
+                        spatial_hash.insert(entity, two_dim_object);
+                    }
+                },
+                ComponentEvent::Removed(id) => spatial_hash.remove(id),
+            }
+        }
+    }
+
+    fn setup(&mut self, res: &mut Resources) {
+        Self::SystemData::setup(res);
+        self.reader = Some(WriteStorage::<TwoDimObject>::fetch(res).register_reader());
+    }
+}
\ No newline at end of file
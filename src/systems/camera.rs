@@ -0,0 +1,160 @@
+use amethyst::{
+    core::{Transform, timing::Time},
+    ecs::{Entities, Join, Read, ReadExpect, ReadStorage, System, WriteStorage},
+    renderer::{Camera, Projection, ScreenDimensions},
+};
+use crate::{
+    components::{BarEdge, CameraFollow, LetterboxBar},
+    level::Bounds,
+};
+
+pub struct CameraFollowSystem;
+
+impl<'s> System<'s> for CameraFollowSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, CameraFollow>,
+        WriteStorage<'s, Transform>,
+        Read<'s, Bounds>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, follows, mut transforms, bounds, time): Self::SystemData) {
+        for (camera_entity, follow) in (&entities, &follows).join() {
+            let (target_x, target_y) = match transforms.get(follow.target) {
+                Some(transform) => (transform.translation().x, transform.translation().y),
+                None => continue,
+            };
+            let transform = match transforms.get_mut(camera_entity) {
+                Some(transform) => transform,
+                None => continue,
+            };
+            // exponential smoothing, so the camera moves the same way at any frame rate
+            let catch_up = 1. - (-follow.smoothing * time.delta_seconds()).exp();
+
+            // the camera transform is the bottom left corner of the view
+            let center_x = transform.translation().x + follow.view_size.x / 2.;
+            let center_y = transform.translation().y + follow.view_size.y / 2.;
+
+            let center_x = center_x + (follow_axis(center_x, target_x, follow.dead_zone.x) - center_x) * catch_up;
+            let center_y = center_y + (follow_axis(center_y, target_y, follow.dead_zone.y) - center_y) * catch_up;
+
+            let center_x = clamp_axis(center_x, bounds.left, bounds.right, follow.view_size.x);
+            let center_y = clamp_axis(center_y, bounds.bottom, bounds.top, follow.view_size.y);
+
+            transform.set_x(center_x - follow.view_size.x / 2.);
+            transform.set_y(center_y - follow.view_size.y / 2.);
+        }
+    }
+}
+
+/// Where the center of the view should be so the target is just inside the dead zone.
+fn follow_axis(center: f32, target: f32, dead_zone: f32) -> f32 {
+    if target > center + dead_zone {
+        target - dead_zone
+    } else if target < center - dead_zone {
+        target + dead_zone
+    } else {
+        center
+    }
+}
+
+fn clamp_axis(center: f32, min: f32, max: f32, view_size: f32) -> f32 {
+    if max - min <= view_size {
+        // the level is smaller than the view, so keep it centered
+        (min + max) / 2.
+    } else {
+        center.max(min + view_size / 2.).min(max - view_size / 2.)
+    }
+}
+
+
+/// Keeps the camera's view the same shape as its virtual resolution when the window is resized,
+/// covering the rest of the window with `LetterboxBar`s.
+#[derive(Default)]
+pub struct CameraResizeSystem {
+    last_dimensions: Option<(f32, f32)>,
+}
+
+impl<'s> System<'s> for CameraResizeSystem {
+    type SystemData = (
+        ReadExpect<'s, ScreenDimensions>,
+        ReadStorage<'s, CameraFollow>,
+        WriteStorage<'s, Camera>,
+        ReadStorage<'s, LetterboxBar>,
+        WriteStorage<'s, Transform>,
+    );
+
+    fn run(&mut self, (screen_dimensions, follows, mut cameras, bars, mut transforms): Self::SystemData) {
+        let dimensions = (screen_dimensions.width(), screen_dimensions.height());
+        // a minimized window has no size, and there is no view to fit into it. it is resized again when
+        // the window is restored
+        if self.last_dimensions == Some(dimensions) || dimensions.0 <= 0. || dimensions.1 <= 0. {
+            return;
+        }
+        self.last_dimensions = Some(dimensions);
+
+        let window_aspect_ratio = dimensions.0 / dimensions.1;
+
+        for (follow, camera) in (&follows, &mut cameras).join() {
+            let view = &follow.view_size;
+            let (margin_x, margin_y) = if window_aspect_ratio > view.x / view.y {
+                // window is wider than the view, so pillarbox
+                ((view.y * window_aspect_ratio - view.x) / 2., 0.)
+            } else {
+                // window is taller than the view, so letterbox
+                (0., (view.x / window_aspect_ratio - view.y) / 2.)
+            };
+
+            *camera = Camera::from(Projection::orthographic(
+                -margin_x,
+                view.x + margin_x,
+                -margin_y,
+                view.y + margin_y,
+            ));
+
+            // bars are children of the camera, so they are positioned relative to the view's bottom left corner
+            for (bar, transform) in (&bars, &mut transforms).join() {
+                let (x, y, width, height) = match (bar.edge, margin_x > 0.) {
+                    (BarEdge::Start, true) => (-margin_x / 2., view.y / 2., margin_x, view.y),
+                    (BarEdge::End, true) => (view.x + margin_x / 2., view.y / 2., margin_x, view.y),
+                    (BarEdge::Start, false) => (view.x / 2., -margin_y / 2., view.x, margin_y),
+                    (BarEdge::End, false) => (view.x / 2., view.y + margin_y / 2., view.x, margin_y),
+                };
+                transform.set_x(x);
+                transform.set_y(y);
+                transform.set_scale(width, height, 1.);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{clamp_axis, follow_axis};
+
+    #[test]
+    fn target_inside_the_dead_zone_leaves_the_view_alone() {
+        assert_eq!(follow_axis(500., 540., 100.), 500.);
+        assert_eq!(follow_axis(500., 400., 100.), 500.);
+    }
+
+    #[test]
+    fn target_past_the_dead_zone_drags_the_view_after_it() {
+        assert_eq!(follow_axis(500., 650., 100.), 550.);
+        assert_eq!(follow_axis(500., 320., 100.), 420.);
+    }
+
+    #[test]
+    fn view_stops_at_the_edges_of_the_level() {
+        assert_eq!(clamp_axis(100., 0., 2000., 1000.), 500.);
+        assert_eq!(clamp_axis(1900., 0., 2000., 1000.), 1500.);
+        assert_eq!(clamp_axis(800., 0., 2000., 1000.), 800.);
+    }
+
+    #[test]
+    fn level_smaller_than_the_view_is_centered() {
+        assert_eq!(clamp_axis(100., 0., 600., 1000.), 300.);
+        assert_eq!(clamp_axis(900., 0., 600., 1000.), 300.);
+    }
+}
\ No newline at end of file
@@ -1,49 +1,158 @@
 use amethyst::{
-    ecs::{Entities, Join, Read, ReadStorage, System, WriteStorage},
-    input::{InputHandler},
+    ecs::{Join, Read, ReadStorage, System, WriteStorage},
 };
 use crate::{
-    PLAYER_MAX_X_VELOCITY,
-    components::{Player, TwoDimObject}
+    collision::{CONTACT_EPSILON, within},
+    components::{Climbable, OneWayPlatform, Player, RigidBody, TwoDimObject, Velocity},
+    config::MovementConfig,
+    systems::{FixedTimestep, PlayerInput, SpatialHash},
 };
 
 pub struct ControlSystem;
 
 impl<'s> System<'s> for ControlSystem {
     type SystemData = (
-        Entities<'s>,
         WriteStorage<'s, Player>,
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, RigidBody>,
         ReadStorage<'s, TwoDimObject>,
-        Read<'s, InputHandler<String, String>>,
+        ReadStorage<'s, OneWayPlatform>,
+        ReadStorage<'s, Climbable>,
+        Read<'s, PlayerInput>,
+        Read<'s, FixedTimestep>,
+        Read<'s, MovementConfig>,
+        Read<'s, SpatialHash>,
     );
 
-    fn run(&mut self, (entities, mut players, two_dim_objects, input): Self::SystemData) {
-        // calculate this so we know if the character should be able to jump
-        let mut player_entities_on_ground = vec![];
+    fn run(&mut self, (mut players, mut velocities, mut rigid_bodies, two_dim_objects, one_way_platforms, climbables, input, timestep, movement, spatial_hash): Self::SystemData) {
+        // input is only sampled once per frame, so apply it for every physics step this frame
+        let dt = timestep.steps() as f32 * timestep.step_seconds();
 
-        for (player, player_entity) in (&players, &entities).join() {
-            for two_dim_object in (&two_dim_objects).join() {
-                if player.two_dim.bottom() == two_dim_object.top() {
-                    player_entities_on_ground.push(player_entity);
+        for (player, velocity, rigid_body, two_dim_object) in (&mut players, &mut velocities, &mut rigid_bodies, &two_dim_objects).join() {
+            let x_input = input.x;
+            let jump_input = input.jump;
+
+            let jump_pressed = jump_input && !player.jump_held;
+            player.jump_held = jump_input;
+            let dash_pressed = input.dash && !player.dash_held;
+            player.dash_held = input.dash;
+
+            let on_one_way_platform = two_dim_object.contacts.ground
+                .is_some_and(|ground| one_way_platforms.contains(ground));
+            let on_solid_ground = two_dim_object.contacts.on_ground() && !on_one_way_platform;
+
+            // up grabs on anywhere in a climbable zone. down only does when there's somewhere lower to
+            // go, and not along with jump, which drops through a one-way platform instead
+            let in_climbable = spatial_hash
+                .query(two_dim_object.left(), two_dim_object.bottom() - CONTACT_EPSILON, two_dim_object.right(), two_dim_object.top())
+                .into_iter()
+                .filter(|&other| climbables.contains(other))
+                .filter_map(|other| two_dim_objects.get(other))
+                .any(|zone| within(two_dim_object, zone));
+            let grab = input.y > 0. || (input.y < 0. && !on_solid_ground && !jump_input);
+            if in_climbable && !rigid_body.climbing && grab {
+                rigid_body.climbing = true;
+                player.jump_rising = false;
+                player.wall_jump_lock = 0;
+                player.dash_steps_left = 0;
+                rigid_body.dashing = false;
+            }
+            if rigid_body.climbing && input.y < 0. && on_solid_ground {
+                // climbed all the way down
+                rigid_body.climbing = false;
+            }
+
+            // holding down on the ground crouches. PhysicsSystem resizes the body, and keeps the player
+            // crouched while there's no room to stand
+            player.wants_to_crouch = input.y < 0. && two_dim_object.contacts.on_ground() && !rigid_body.climbing;
+
+            if rigid_body.climbing {
+                velocity.x = x_input * movement.climb_velocity;
+                velocity.y = input.y * movement.climb_velocity;
+                if jump_pressed {
+                    rigid_body.climbing = false;
+                    velocity.y = movement.jump_velocity();
+                    player.jump_rising = true;
+                    player.jump_buffer = 0;
                 }
+                continue;
             }
-        }
 
-        for (mut player, player_entity) in (&mut players, &entities).join() {
-            let player_on_ground = player_entities_on_ground.contains(&player_entity);
+            // a dash goes the way the player faces, at a fixed speed and height. there's one per airtime,
+            // and a cooldown after each
+            if player.dash_steps_left == 0 && x_input != 0. {
+                player.facing = x_input.signum();
+            }
+            let can_dash = player.dash_steps_left == 0 && player.dash_cooldown == 0
+                && (two_dim_object.contacts.on_ground() || player.air_dash_available);
+            if dash_pressed && can_dash {
+                if !two_dim_object.contacts.on_ground() {
+                    player.air_dash_available = false;
+                }
+                player.dash_steps_left = movement.dash_duration_frames;
+                player.jump_rising = false;
+                rigid_body.dashing = true;
+            }
+            if player.dash_steps_left > 0 {
+                velocity.x = player.facing * movement.dash_velocity(timestep.step_seconds());
+                velocity.y = 0.;
+                continue;
+            }
+
+            // input is ignored for a moment after a wall jump, so the push away from the wall isn't undone
+            if player.wall_jump_lock == 0 {
+                if x_input == 0. {
+                    velocity.x = 0.;
+                } else {
+                    let max_x_velocity = if player.crouching { movement.crouch_x_velocity } else { movement.max_x_velocity };
+                    velocity.x += movement.x_acceleration * x_input * dt;
+                    velocity.x = velocity.x.min(max_x_velocity).max(-1. * max_x_velocity);
+                }
+            }
 
-            let x_input = input.axis_value("horizontal").expect("horizontal axis exists");
-            let jump_input = input.action_is_down("jump").expect("jump action exists");
+            // releasing jump on the way up cuts the jump short, so a tap gives a small hop
+            if player.jump_rising {
+                if velocity.y <= 0. {
+                    player.jump_rising = false;
+                } else if !jump_input {
+                    velocity.y = velocity.y.min(movement.jump_release_velocity());
+                    player.jump_rising = false;
+                }
+            }
 
-            if x_input == 0. {
-                player.two_dim.velocity.x = 0.;
-            } else {
-                player.two_dim.velocity.x += 0.1 * x_input as f32;
-                player.two_dim.velocity.x = player.two_dim.velocity.x.min(PLAYER_MAX_X_VELOCITY).max(-1. * PLAYER_MAX_X_VELOCITY);
+            // a press shortly before landing is remembered, so the jump happens on the first step on the ground
+            if jump_input {
+                player.jump_buffer = movement.jump_buffer_frames;
             }
+            // coyote time lets the player jump for a few steps after walking off a ledge
+            let player_can_jump = player.steps_since_grounded <= movement.coyote_time_frames;
 
-            if jump_input && player_on_ground {
-                player.two_dim.velocity.y = 20.;
+            if jump_pressed && input.y < 0. && on_one_way_platform {
+                // down and jump drops through the platform instead of jumping
+                rigid_body.dropping_through = true;
+                player.jump_buffer = 0;
+                player.steps_since_grounded = u32::MAX;
+            } else if (jump_input || player.jump_buffer > 0) && player_can_jump {
+                velocity.y = movement.jump_velocity();
+                player.jump_rising = true;
+                player.jump_buffer = 0;
+                // stops the rest of the coyote time being used for a second jump
+                player.steps_since_grounded = u32::MAX;
+            } else if jump_pressed && two_dim_object.contacts.on_wall() {
+                // jumping off a wall pushes the player away from it. only a new press counts, otherwise
+                // holding jump would climb a wall by bouncing off it over and over
+                let away = if two_dim_object.contacts.left.is_some() { 1. } else { -1. };
+                velocity.x = away * movement.wall_jump_x_velocity;
+                velocity.y = movement.jump_velocity();
+                player.jump_rising = true;
+                player.jump_buffer = 0;
+                player.wall_jump_lock = movement.wall_jump_lock_frames;
+            } else if jump_pressed && player.air_jumps_left > 0 {
+                velocity.y = movement.jump_velocity();
+                player.jump_rising = true;
+                player.jump_buffer = 0;
+                player.air_jumps_left -= 1;
+                player.air_jump_started = true;
             };
         }
     }
@@ -0,0 +1,95 @@
+use amethyst::{
+    ecs::{Read, System, Write},
+    input::{InputHandler},
+};
+use serde_derive::{Deserialize, Serialize};
+
+/// What the player is asking for this frame, decoupled from where the input came from.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+pub struct PlayerInput {
+    pub x: f32,
+    /// Up is positive, for climbing and dropping through one-way platforms.
+    #[serde(default)]
+    pub y: f32,
+    pub jump: bool,
+    #[serde(default)]
+    pub dash: bool,
+}
+
+/// Reads the keyboard bindings from `bindings_config.ron` into `PlayerInput`.
+pub struct PlayerInputSystem;
+
+impl<'s> System<'s> for PlayerInputSystem {
+    type SystemData = (
+        Read<'s, InputHandler<String, String>>,
+        Write<'s, PlayerInput>,
+    );
+
+    fn run(&mut self, (input, mut player_input): Self::SystemData) {
+        player_input.x = input.axis_value("horizontal").expect("horizontal axis exists") as f32;
+        player_input.y = input.axis_value("vertical").expect("vertical axis exists") as f32;
+        player_input.jump = input.action_is_down("jump").expect("jump action exists");
+        player_input.dash = input.action_is_down("dash").expect("dash action exists");
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub struct ScriptStep {
+    pub frames: usize,
+    pub input: PlayerInput,
+}
+
+/// A sequence of inputs to replay, each held for a number of frames.
+#[derive(Serialize, Deserialize, Default, Clone, PartialEq, Debug)]
+pub struct InputScript {
+    pub steps: Vec<ScriptStep>,
+}
+
+impl InputScript {
+    pub fn new() -> Self {
+        InputScript::default()
+    }
+
+    pub fn hold(mut self, input: PlayerInput, frames: usize) -> Self {
+        self.steps.push(ScriptStep { frames, input });
+        self
+    }
+
+    pub fn frames(&self) -> usize {
+        self.steps.iter().map(|step| step.frames).sum()
+    }
+
+    /// Input for the given frame, or no input once the script has run out.
+    pub fn input_at(&self, frame: usize) -> PlayerInput {
+        let mut start = 0;
+        for step in &self.steps {
+            if frame < start + step.frames {
+                return step.input;
+            }
+            start += step.frames;
+        }
+
+        PlayerInput::default()
+    }
+}
+
+/// Replays an `InputScript` into `PlayerInput`, one entry per frame, in place of `PlayerInputSystem`.
+pub struct ScriptedInputSystem {
+    script: InputScript,
+    frame: usize,
+}
+
+impl ScriptedInputSystem {
+    pub fn new(script: InputScript) -> Self {
+        ScriptedInputSystem { script, frame: 0 }
+    }
+}
+
+impl<'s> System<'s> for ScriptedInputSystem {
+    type SystemData = Write<'s, PlayerInput>;
+
+    fn run(&mut self, mut player_input: Self::SystemData) {
+        *player_input = self.script.input_at(self.frame);
+        self.frame += 1;
+    }
+}
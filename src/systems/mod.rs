@@ -1,3 +1,12 @@
+mod input;
+pub use input::{InputScript, PlayerInput, PlayerInputSystem, ScriptStep, ScriptedInputSystem};
+
+mod timestep;
+pub use timestep::{FixedTimestep, FixedTimestepSystem};
+
+mod broadphase;
+pub use broadphase::{SpatialHash, SpatialHashSystem};
+
 mod control;
 pub use control::ControlSystem;
 
@@ -5,4 +14,7 @@ mod physics;
 pub use physics::PhysicsSystem;
 
 mod animation;
-pub use animation::AnimationSystem;
\ No newline at end of file
+pub use animation::AnimationSystem;
+
+mod camera;
+pub use camera::{CameraFollowSystem, CameraResizeSystem};
\ No newline at end of file
@@ -1,110 +1,533 @@
 use amethyst::{
-    ecs::{Join, ReadStorage, System, WriteStorage},
+    core::Transform,
+    ecs::{Entities, Entity, Join, Read, ReadStorage, System, Write, WriteStorage},
 };
 use crate::{
-    DISPLAY_WIDTH, PLAYER_W,
-    components::{Player, TwoDimObject}
+    collision::{CONTACT_EPSILON, Faces, Hits, find_contacts, find_slope, penetrating, slope_wall, sweep, sweep_x, sweep_y, within},
+    components::{Climbable, MovingPlatform, OneWayPlatform, Player, Pushable, RigidBody, Slope, TwoDimObject, TwoDimVector, Velocity},
+    config::MovementConfig,
+    level::Bounds,
+    systems::{FixedTimestep, SpatialHash},
+    PLAYER_H,
 };
 
 pub struct PhysicsSystem;
 
 impl<'s> System<'s> for PhysicsSystem {
     type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, TwoDimObject>,
+        WriteStorage<'s, Velocity>,
+        WriteStorage<'s, RigidBody>,
+        ReadStorage<'s, OneWayPlatform>,
+        ReadStorage<'s, Slope>,
+        ReadStorage<'s, Climbable>,
+        ReadStorage<'s, Pushable>,
+        WriteStorage<'s, MovingPlatform>,
         WriteStorage<'s, Player>,
-        ReadStorage<'s, TwoDimObject>,
+        WriteStorage<'s, Transform>,
+        Write<'s, SpatialHash>,
+        Read<'s, FixedTimestep>,
+        Read<'s, MovementConfig>,
+        Read<'s, Bounds>,
     );
 
-    fn run(&mut self, (mut players, two_dim_objects): Self::SystemData) {
-        for mut player in (&mut players).join() {
-            if player.two_dim.velocity.x > 0. {
-                // player moving right
-                let old_x = player.two_dim.right();
-                let mut possible_new_x = old_x + player.two_dim.velocity.x;
-
-                for two_dim_object in (&two_dim_objects).join() {
-                    if player.two_dim.overlapping_y(two_dim_object)
-                        && old_x <= two_dim_object.left()
-                        && possible_new_x >= two_dim_object.left() {
-                        // can't early return here, because we need to consider collision with more than one other object
-                        // don't need to set velocity back to zero here, but could depending on how we want the player animation to act
-                        possible_new_x = two_dim_object.left();
-                    }
+    fn run(
+        &mut self,
+        (entities, mut two_dim_objects, mut velocities, mut rigid_bodies, one_way_platforms, slopes, climbables, pushables, mut moving_platforms, mut players, mut transforms, mut spatial_hash, timestep, movement, bounds): Self::SystemData,
+    ) {
+        // bodies move one at a time in entity order, so the outcome of two bodies colliding is always the same
+        let bodies: Vec<Entity> = (&entities, &rigid_bodies, &velocities, &two_dim_objects).join()
+            .map(|(entity, _, _, _)| entity)
+            .collect();
+        let platforms: Vec<Entity> = (&entities, &moving_platforms, &two_dim_objects).join()
+            .map(|(entity, _, _)| entity)
+            .collect();
+        let kinds = Kinds {
+            one_way_platforms: &one_way_platforms,
+            slopes: &slopes,
+            climbables: &climbables,
+            pushables: &pushables,
+        };
+
+        for _ in 0..timestep.steps() {
+            for &entity in bodies.iter().chain(&platforms) {
+                let two_dim_object = two_dim_objects.get_mut(entity).expect("moving object without a TwoDimObject");
+                two_dim_object.previous_position = two_dim_object.position;
+            }
+
+            // platforms go first, so bodies riding them are carried before they move themselves
+            for &platform in &platforms {
+                let moving_platform = moving_platforms.get_mut(platform).expect("platform without a MovingPlatform");
+                step_platform(platform, moving_platform, &bodies, &mut two_dim_objects, &kinds, &mut spatial_hash, timestep.step_seconds());
+            }
+
+            for &entity in &bodies {
+                let rigid_body = rigid_bodies.get_mut(entity).expect("rigid body without a RigidBody component");
+                let velocity = velocities.get_mut(entity).expect("rigid body without a velocity");
+                // players shove pushable bodies out of their way before moving themselves
+                if players.contains(entity) {
+                    let distance_x = velocity.x * timestep.step_seconds();
+                    clear_the_way(entity, distance_x, 0, &mut two_dim_objects, &mut spatial_hash, &kinds, &bounds);
                 }
-                // ensure player stays inside "walls" of display
-                let new_x = possible_new_x.min(DISPLAY_WIDTH).max(PLAYER_W as f32);
-                player.two_dim.set_right(new_x);
-            } else if player.two_dim.velocity.x < 0. {
-                // player moving left
-                let old_x = player.two_dim.left();
-                let mut possible_new_x = old_x + player.two_dim.velocity.x;
-
-                for two_dim_object in (&two_dim_objects).join() {
-                    if player.two_dim.overlapping_y(two_dim_object)
-                        && old_x >= two_dim_object.right()
-                        && possible_new_x <= two_dim_object.right() {
-                        // can't early return here, because we need to consider collision with more than one other object
-                        // don't need to set velocity back to zero here, but could depending on how we want the player animation to act
-                        possible_new_x = two_dim_object.right();
+                if let Some(player) = players.get_mut(entity) {
+                    let colliders = Colliders {
+                        spatial_hash: &spatial_hash,
+                        two_dim_objects: &two_dim_objects,
+                        kinds: &kinds,
+                    };
+                    let mut body = *two_dim_objects.get(entity).expect("rigid body without a TwoDimObject");
+                    if crouch_or_stand(entity, player, &mut body, &colliders, &movement) {
+                        *two_dim_objects.get_mut(entity).expect("rigid body without a TwoDimObject") = body;
+                        spatial_hash.insert(entity, &body);
                     }
                 }
-                // ensure player stays inside "walls" of display
-                let new_x = possible_new_x.min(DISPLAY_WIDTH - PLAYER_W as f32).max(0.);
-                player.two_dim.set_left(new_x);
-            };
 
-            let player_on_ground = if player.two_dim.velocity.y > 0. {
-                let old_y = player.two_dim.top();
-                let possible_new_y = player.two_dim.top() + player.two_dim.velocity.y;
-                let mut new_y = possible_new_y;
-
-                for two_dim_object in (&two_dim_objects).join() {
-                    if player.two_dim.overlapping_x(two_dim_object)
-                        && old_y <= two_dim_object.bottom()
-                        && new_y >= two_dim_object.bottom() {
-                        new_y = two_dim_object.bottom();
-                        player.two_dim.velocity.y = 0.;
-                    }
+                let mut body = *two_dim_objects.get(entity).expect("rigid body without a TwoDimObject");
+                let substep_count = substeps(&body, velocity, timestep.step_seconds());
+                for _ in 0..substep_count {
+                    let colliders = Colliders {
+                        spatial_hash: &spatial_hash,
+                        two_dim_objects: &two_dim_objects,
+                        kinds: &kinds,
+                    };
+                    body = step_body(entity, rigid_body, velocity, &colliders, &movement, &bounds, timestep.step_seconds() / substep_count as f32);
+                    *two_dim_objects.get_mut(entity).expect("rigid body without a TwoDimObject") = body;
+                    // later bodies this step collide with where this one ended up
+                    spatial_hash.insert(entity, &body);
                 }
-                player.two_dim.set_top(new_y);
-
-                false
-            } else if player.two_dim.velocity.y < 0. {
-                let old_y = player.two_dim.bottom();
-                let possible_new_y = player.two_dim.bottom() + player.two_dim.velocity.y;
-                let mut new_y = possible_new_y;
-                let mut player_on_ground = false;
-
-                for two_dim_object in (&two_dim_objects).join() {
-                    if player.two_dim.overlapping_x(two_dim_object)
-                        && old_y >= two_dim_object.top()
-                        && new_y <= two_dim_object.top() {
-                        player_on_ground = true;
-                        new_y = two_dim_object.top();
-                        player.two_dim.velocity.y = 0.;
-                    }
+
+                if let Some(player) = players.get_mut(entity) {
+                    step_player(player, &body, rigid_body, velocity, &movement);
                 }
-                player.two_dim.set_bottom(new_y);
+            }
+        }
 
-                player_on_ground
-            } else {
-                let mut player_on_ground = false;
+        for &entity in bodies.iter().chain(&platforms) {
+            if let (Some(two_dim_object), Some(transform)) = (two_dim_objects.get(entity), transforms.get_mut(entity)) {
+                two_dim_object.update_transform_position_interpolated(transform, timestep.alpha());
+            }
+        }
+    }
+}
 
-                for two_dim_object in (&two_dim_objects).join() {
-                    if player.two_dim.overlapping_x(two_dim_object)
-                        && player.two_dim.bottom() == two_dim_object.top() {
-                        player_on_ground = true;
-                    }
-                }
+/// Most sub-steps a single body is split into per physics step, however fast it's going.
+const MAX_SUBSTEPS: u32 = 8;
+
+/// Number of sub-steps to move the body in this physics step, so it goes no further than half its own
+/// size in each. The sweeps alone stop fast bodies at the first thing they hit, but slopes, one-way
+/// platforms, climbables and contacts are only checked where each sub-step ends up.
+fn substeps(body: &TwoDimObject, velocity: &Velocity, dt: f32) -> u32 {
+    let distance = velocity.x.abs().max(velocity.y.abs()) * dt;
+    let max_distance = body.size.x.min(body.size.y) / 2.;
+    ((distance / max_distance).ceil() as u32).max(1).min(MAX_SUBSTEPS)
+}
+
+/// How many pushable bodies in a row, or stacked on top of each other, a single shove can move.
+const MAX_SHOVE_CHAIN: u32 = 16;
+
+/// Which objects collide in some other way than as a solid box.
+struct Kinds<'a, 'e> {
+    one_way_platforms: &'a ReadStorage<'e, OneWayPlatform>,
+    slopes: &'a ReadStorage<'e, Slope>,
+    climbables: &'a ReadStorage<'e, Climbable>,
+    pushables: &'a ReadStorage<'e, Pushable>,
+}
+
+/// Everything a body can collide with.
+struct Colliders<'a, 'e> {
+    spatial_hash: &'a SpatialHash,
+    two_dim_objects: &'a WriteStorage<'e, TwoDimObject>,
+    kinds: &'a Kinds<'a, 'e>,
+}
 
-                player_on_ground
+/// Objects near a body, split up by how the body collides with them.
+struct Nearby<'a> {
+    solid: Vec<(Entity, &'a TwoDimObject)>,
+    one_way: Vec<(Entity, &'a TwoDimObject)>,
+    slopes: Vec<(Entity, &'a TwoDimObject, &'a Slope)>,
+    /// Not collided with at all, only held on to.
+    climbable: Vec<(Entity, &'a TwoDimObject)>,
+}
+
+impl<'a, 'e> Colliders<'a, 'e> {
+    /// Objects other than the body near the area.
+    fn near(&self, body: Entity, left: f32, bottom: f32, right: f32, top: f32) -> Nearby<'a> {
+        let mut nearby = Nearby { solid: vec![], one_way: vec![], slopes: vec![], climbable: vec![] };
+
+        let others = self.spatial_hash.query(left - CONTACT_EPSILON, bottom - CONTACT_EPSILON, right + CONTACT_EPSILON, top + CONTACT_EPSILON);
+        for other in others.into_iter().filter(|&other| other != body) {
+            let two_dim_object = match self.two_dim_objects.get(other) {
+                Some(two_dim_object) => two_dim_object,
+                None => continue,
             };
+            if let Some(slope) = self.kinds.slopes.get(other) {
+                nearby.slopes.push((other, two_dim_object, slope));
+            } else if self.kinds.climbables.contains(other) {
+                nearby.climbable.push((other, two_dim_object));
+            } else if self.kinds.one_way_platforms.contains(other) {
+                nearby.one_way.push((other, two_dim_object));
+            } else {
+                nearby.solid.push((other, two_dim_object));
+            }
+        }
+
+        nearby
+    }
+
+    /// Whether the body would fit in the area without overlapping anything solid. Slopes only get in the
+    /// way from below, since bodies standing on them sink into their boxes.
+    fn is_free(&self, body: Entity, area: &TwoDimObject) -> bool {
+        let nearby = self.near(body, area.left(), area.bottom(), area.right(), area.top());
+        let blocked_by_solid = nearby.solid.iter().any(|&(_, other)| penetrating(area, other));
+        let blocked_by_slope = nearby.slopes.iter()
+            .any(|&(_, other, _)| area.bottom() < other.bottom() && penetrating(area, other));
+
+        !blocked_by_solid && !blocked_by_slope
+    }
+
+    fn slope(&self, entity: Entity) -> Option<(&'a TwoDimObject, &'a Slope)> {
+        Some((self.two_dim_objects.get(entity)?, self.kinds.slopes.get(entity)?))
+    }
+}
+
+/// Moves the body `distance` along x, stopping against the nearest obstacle in the way and at the edges
+/// of the level.
+///
+/// Returns the obstacle hit, if any.
+fn sweep_body_x(entity: Entity, body: &mut TwoDimObject, distance: f32, colliders: &Colliders, bounds: &Bounds) -> Option<Entity> {
+    sweep_body(entity, body, TwoDimVector { x: distance, y: 0. }, false, colliders, bounds).x
+}
+
+/// Moves the body by `distance`, stopping at the first obstacle in the way and sliding along it, and
+/// keeping it within the edges of the level. `catch_on_platforms` is whether one-way platforms stop the
+/// body falling.
+fn sweep_body(
+    entity: Entity,
+    body: &mut TwoDimObject,
+    distance: TwoDimVector<f32>,
+    catch_on_platforms: bool,
+    colliders: &Colliders,
+    bounds: &Bounds,
+) -> Hits {
+    // only objects within the area swept by the body can be hit
+    let nearby = colliders.near(
+        entity,
+        body.left() + distance.x.min(0.),
+        body.bottom() + distance.y.min(0.),
+        body.right() + distance.x.max(0.),
+        body.top() + distance.y.max(0.),
+    );
+    let slope_walls: Vec<_> = nearby.slopes.iter()
+        .map(|&(other, object, slope)| (other, slope_wall(object, slope, distance.x)))
+        .collect();
+    let mut obstacles: Vec<_> = nearby.solid.iter().map(|&(other, object)| (other, object, Faces::All)).collect();
+    // one-way platforms only catch bodies falling onto them. the sweep already ignores platforms the
+    // body started below the top of
+    if catch_on_platforms {
+        obstacles.extend(nearby.one_way.iter().map(|&(other, object)| (other, object, Faces::Top)));
+    }
+    // bodies walk into the high end of a slope, and rise into its flat underside like any other ceiling
+    obstacles.extend(slope_walls.iter().map(|(other, wall)| (*other, wall, Faces::Sides)));
+    obstacles.extend(nearby.slopes.iter().map(|&(other, object, _)| (other, object, Faces::Bottom)));
+
+    // a body standing on a slope sinks into it up to the middle of its bottom edge, so its feet are
+    // lifted by as much, plus however far the slope rises this step, for the sweep. it then steps up
+    // onto the ground at the top of the slope, rather than walking into the side of it
+    let step_up = body.contacts.ground
+        .and_then(|ground| colliders.slope(ground))
+        .map_or(0., |(object, slope)| (body.size.x / 2. + distance.x.abs()) * slope.gradient(object).abs() + CONTACT_EPSILON);
+    let mut feet_lifted = *body;
+    feet_lifted.size.y -= step_up;
+    feet_lifted.position.y += step_up / 2.;
+    let hits = sweep(&mut feet_lifted, distance.x, distance.y, &obstacles);
+    body.position.x = feet_lifted.position.x;
+    body.position.y = feet_lifted.position.y - step_up / 2.;
+
+    // ensure the body stays inside the level
+    let left = body.left().min(bounds.right - body.size.x).max(bounds.left);
+    body.set_left(left);
+
+    hits
+}
+
+/// Shoves the pushable body, if any, which the body would run into moving `distance` along x, so it
+/// makes as much room as it can.
+fn clear_the_way(
+    entity: Entity,
+    distance: f32,
+    depth: u32,
+    two_dim_objects: &mut WriteStorage<TwoDimObject>,
+    spatial_hash: &mut SpatialHash,
+    kinds: &Kinds,
+    bounds: &Bounds,
+) {
+    if distance == 0. || depth >= MAX_SHOVE_CHAIN {
+        return;
+    }
+
+    let (hit, moved) = {
+        let colliders = Colliders { spatial_hash: &*spatial_hash, two_dim_objects: &*two_dim_objects, kinds };
+        let mut probe = *two_dim_objects.get(entity).expect("rigid body without a TwoDimObject");
+        let start_x = probe.position.x;
+        let hit = sweep_body_x(entity, &mut probe, distance, &colliders, bounds);
+        (hit, probe.position.x - start_x)
+    };
 
-            // gravity
-            if player_on_ground {
-                player.two_dim.velocity.y = 0.;
+    if let Some(pushable) = hit.filter(|&other| kinds.pushables.contains(other)) {
+        shove(pushable, distance - moved, depth + 1, two_dim_objects, spatial_hash, kinds, bounds);
+    }
+}
+
+/// Moves a body up to `distance` along x, shoving anything pushable in its way along in front of it and
+/// carrying whatever is standing on it.
+///
+/// Returns how far the body moved.
+fn shove(
+    entity: Entity,
+    distance: f32,
+    depth: u32,
+    two_dim_objects: &mut WriteStorage<TwoDimObject>,
+    spatial_hash: &mut SpatialHash,
+    kinds: &Kinds,
+    bounds: &Bounds,
+) -> f32 {
+    clear_the_way(entity, distance, depth, two_dim_objects, spatial_hash, kinds, bounds);
+
+    let start = *two_dim_objects.get(entity).expect("rigid body without a TwoDimObject");
+    let (body, riders) = {
+        let colliders = Colliders { spatial_hash: &*spatial_hash, two_dim_objects: &*two_dim_objects, kinds };
+        let mut body = start;
+        sweep_body_x(entity, &mut body, distance, &colliders, bounds);
+        let riders: Vec<Entity> = colliders.near(entity, start.left(), start.top(), start.right(), start.top()).solid.iter()
+            .filter(|&&(_, other)| other.contacts.ground == Some(entity))
+            .map(|&(rider, _)| rider)
+            .collect();
+        (body, riders)
+    };
+
+    *two_dim_objects.get_mut(entity).expect("rigid body without a TwoDimObject") = body;
+    spatial_hash.insert(entity, &body);
+
+    let moved = body.position.x - start.position.x;
+    if depth < MAX_SHOVE_CHAIN {
+        for rider in riders {
+            shove(rider, moved, depth + 1, two_dim_objects, spatial_hash, kinds, bounds);
+        }
+    }
+    moved
+}
+
+/// Moves a single body through one physics step and returns where it ended up.
+fn step_body(
+    entity: Entity,
+    rigid_body: &mut RigidBody,
+    velocity: &mut Velocity,
+    colliders: &Colliders,
+    movement: &MovementConfig,
+    bounds: &Bounds,
+    dt: f32,
+) -> TwoDimObject {
+    let mut body = *colliders.two_dim_objects.get(entity).expect("rigid body without a TwoDimObject");
+    let start = body;
+    let rising = velocity.y > 0.;
+
+    let distance = TwoDimVector { x: velocity.x * dt, y: velocity.y * dt };
+    let catch_on_platforms = !rigid_body.dropping_through && !rigid_body.climbing;
+    // x collisions don't stop the body, so it keeps pushing against walls while moving into them
+    if sweep_body(entity, &mut body, distance, catch_on_platforms, colliders, bounds).y.is_some() {
+        velocity.y = 0.;
+    }
+
+    // reaches far enough down to find slopes up to twice as steep as 45° falling away under the body
+    let slope_reach = body.size.x + distance.x.abs();
+    let nearby = colliders.near(entity, body.left(), body.bottom() - slope_reach, body.right(), body.top());
+    if rigid_body.climbing {
+        let zones: Vec<_> = nearby.climbable.iter().filter(|&&(_, zone)| within(&start, zone)).collect();
+        if zones.is_empty() {
+            rigid_body.climbing = false;
+        } else {
+            // climbing stops at the top of the zone, rather than letting go and falling back into it
+            let top = zones.iter().map(|&&(_, zone)| zone.top()).fold(f32::NEG_INFINITY, f32::max);
+            if body.bottom() > top {
+                body.set_bottom(top);
+                velocity.y = 0.;
+            }
+            rigid_body.climbing = zones.iter().any(|&&(_, zone)| within(&body, zone));
+        }
+    }
+    body.contacts = find_contacts(&body, nearby.solid);
+    if rigid_body.dropping_through {
+        // keep ignoring platforms until the body has fallen all the way through them
+        rigid_body.dropping_through = nearby.one_way.iter().any(|&(_, platform)| within(&body, platform));
+    } else if body.contacts.ground.is_none() {
+        // platforms only ever count as ground, never as walls or ceilings
+        body.contacts.ground = find_contacts(&body, nearby.one_way).ground;
+    }
+
+    let sticky = start.contacts.on_ground() && !rising;
+    if let Some((slope, surface)) = find_slope(&body, &start, sticky, nearby.slopes) {
+        // ground the body is already standing on holds it up if it's higher than the slope
+        if body.contacts.ground.is_none() || surface >= body.bottom() {
+            body.set_bottom(surface);
+            body.contacts.ground = Some(slope);
+        }
+    }
+
+    // gravity
+    if rigid_body.climbing || rigid_body.dashing {
+        // climbing and dashing bodies move exactly as fast as they are told to
+    } else if body.contacts.on_ground() {
+        velocity.y = 0.;
+    } else {
+        velocity.y -= movement.gravity * rigid_body.gravity_scale * dt;
+    }
+
+    body
+}
+
+/// Moves a platform one physics step along its path, carrying the bodies riding it and pushing bodies
+/// out of its way. Bodies are handled in entity order. If one can't be pushed clear, because something
+/// else is in the way, the platform stays put for this step rather than crushing it.
+fn step_platform(
+    platform: Entity,
+    moving_platform: &mut MovingPlatform,
+    bodies: &[Entity],
+    two_dim_objects: &mut WriteStorage<TwoDimObject>,
+    kinds: &Kinds,
+    spatial_hash: &mut SpatialHash,
+    dt: f32,
+) {
+    let object = *two_dim_objects.get(platform).expect("platform without a TwoDimObject");
+    let (top_left, progress) = moving_platform.advance(TwoDimVector { x: object.left(), y: object.top() }, dt);
+    let delta = TwoDimVector { x: top_left.x - object.left(), y: top_left.y - object.top() };
+    if delta.x == 0. && delta.y == 0. {
+        moving_platform.progress = progress;
+        return;
+    }
+
+    let mut moved = object;
+    moved.set_left(top_left.x);
+    moved.set_top(top_left.y);
+    // one-way platforms carry bodies standing on them but pass through everything else
+    let pushes_bodies = !kinds.one_way_platforms.contains(platform);
+
+    let mut pushed_bodies = vec![];
+    {
+        let colliders = Colliders {
+            spatial_hash: &*spatial_hash,
+            two_dim_objects: &*two_dim_objects,
+            kinds,
+        };
+
+        for &entity in bodies {
+            let body = *two_dim_objects.get(entity).expect("rigid body without a TwoDimObject");
+            let riding = body.contacts.ground == Some(platform);
+            let push = if riding {
+                delta
+            } else if pushes_bodies && penetrating(&moved, &body) {
+                push_out(&moved, &body, &delta)
             } else {
-                player.two_dim.velocity.y -= 0.7;
+                continue;
+            };
+
+            let mut pushed = body;
+            let nearby = colliders.near(
+                entity,
+                body.left() + push.x.min(0.),
+                body.bottom() + push.y.min(0.),
+                body.right() + push.x.max(0.),
+                body.top() + push.y.max(0.),
+            );
+            // the platform is what's doing the pushing, so it can't get in the way
+            let solid: Vec<_> = nearby.solid.into_iter().filter(|&(other, _)| other != platform).collect();
+            sweep_x(&mut pushed, push.x, solid.iter().cloned());
+            let blocked_y = sweep_y(&mut pushed, push.y, solid.iter().cloned()).is_some();
+            if riding && !blocked_y {
+                // stay exactly on top, so the rider doesn't drift off through rounding
+                pushed.set_bottom(moved.top());
+            }
+
+            if pushes_bodies && penetrating(&moved, &pushed) {
+                // the body would be crushed, so the platform waits instead
+                return;
             }
+            pushed_bodies.push((entity, pushed));
         }
     }
+
+    *two_dim_objects.get_mut(platform).expect("platform without a TwoDimObject") = moved;
+    spatial_hash.insert(platform, &moved);
+    moving_platform.progress = progress;
+
+    for (entity, pushed) in pushed_bodies {
+        *two_dim_objects.get_mut(entity).expect("rigid body without a TwoDimObject") = pushed;
+        spatial_hash.insert(entity, &pushed);
+    }
+}
+
+/// How far a body overlapping a platform has to move to be clear of it, in the direction the platform
+/// is mostly moving in.
+fn push_out(platform: &TwoDimObject, body: &TwoDimObject, delta: &TwoDimVector<f32>) -> TwoDimVector<f32> {
+    if delta.y.abs() >= delta.x.abs() {
+        let y = if delta.y > 0. { platform.top() - body.bottom() } else { platform.bottom() - body.top() };
+        TwoDimVector { x: 0., y }
+    } else {
+        let x = if delta.x > 0. { platform.right() - body.left() } else { platform.left() - body.right() };
+        TwoDimVector { x, y: 0. }
+    }
 }
+
+/// Shrinks the player's body down to `crouch_height` when they want to crouch, or grows it back to its
+/// full height once there's room overhead, keeping their feet where they are.
+///
+/// Returns whether the body changed size.
+fn crouch_or_stand(entity: Entity, player: &mut Player, body: &mut TwoDimObject, colliders: &Colliders, movement: &MovementConfig) -> bool {
+    if player.wants_to_crouch == player.crouching {
+        return false;
+    }
+
+    let mut resized = *body;
+    resized.size.y = if player.wants_to_crouch { movement.crouch_height } else { PLAYER_H as f32 };
+    resized.set_bottom(body.bottom());
+    // the previous position moves along too, so the change in size isn't drawn as sliding up or down
+    resized.previous_position.y += resized.position.y - body.position.y;
+    if !player.wants_to_crouch && !colliders.is_free(entity, &resized) {
+        return false;
+    }
+
+    player.crouching = player.wants_to_crouch;
+    *body = resized;
+    true
+}
+
+/// Player bookkeeping which depends on where the player's body ended up after a physics step.
+fn step_player(player: &mut Player, body: &TwoDimObject, rigid_body: &mut RigidBody, velocity: &mut Velocity, movement: &MovementConfig) {
+    if player.dash_steps_left > 0 {
+        player.dash_steps_left -= 1;
+        if player.dash_steps_left == 0 {
+            // the dash ends back within the usual speed limit
+            rigid_body.dashing = false;
+            player.dash_cooldown = movement.dash_cooldown_frames;
+            velocity.x = velocity.x.min(movement.max_x_velocity).max(-movement.max_x_velocity);
+        }
+    } else {
+        player.dash_cooldown = player.dash_cooldown.saturating_sub(1);
+    }
+
+    if body.contacts.on_ground() {
+        player.steps_since_grounded = 0;
+        player.air_jumps_left = movement.air_jumps;
+        player.air_dash_available = true;
+    } else {
+        player.steps_since_grounded = player.steps_since_grounded.saturating_add(1);
+
+        // pushing into a wall while falling slows the fall down to a slide. physics leaves the velocity
+        // into the wall alone, so it says which way the player is holding
+        if body.contacts.pushing_into_wall(velocity.x) && !rigid_body.climbing {
+            velocity.y = velocity.y.max(-movement.wall_slide_velocity);
+        }
+    }
+    player.jump_buffer = player.jump_buffer.saturating_sub(1);
+    player.wall_jump_lock = player.wall_jump_lock.saturating_sub(1);
+}
\ No newline at end of file
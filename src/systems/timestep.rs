@@ -0,0 +1,68 @@
+use amethyst::{
+    core::timing::Time,
+    ecs::{Read, System, Write},
+};
+use crate::{MAX_PHYSICS_STEPS_PER_FRAME, PHYSICS_STEP_SECONDS};
+
+/// Accumulates frame time and tells the simulation systems how many fixed steps to run this frame.
+pub struct FixedTimestep {
+    step_seconds: f32,
+    accumulator: f32,
+    steps: u32,
+}
+
+impl Default for FixedTimestep {
+    fn default() -> Self {
+        FixedTimestep::new(PHYSICS_STEP_SECONDS)
+    }
+}
+
+impl FixedTimestep {
+    pub fn new(step_seconds: f32) -> Self {
+        FixedTimestep {
+            step_seconds,
+            accumulator: 0.,
+            steps: 0,
+        }
+    }
+
+    pub fn advance(&mut self, delta_seconds: f32) {
+        self.accumulator += delta_seconds;
+        let steps = (self.accumulator / self.step_seconds) as u32;
+        self.accumulator -= steps as f32 * self.step_seconds;
+
+        if steps > MAX_PHYSICS_STEPS_PER_FRAME {
+            // we can't keep up, so drop the extra time rather than falling further behind every frame
+            self.steps = MAX_PHYSICS_STEPS_PER_FRAME;
+        } else {
+            self.steps = steps;
+        }
+    }
+
+    pub fn step_seconds(&self) -> f32 {
+        self.step_seconds
+    }
+
+    /// Number of fixed steps to simulate this frame.
+    pub fn steps(&self) -> u32 {
+        self.steps
+    }
+
+    /// How far the current frame is between the last physics step and the next one, from 0 to 1.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator / self.step_seconds
+    }
+}
+
+pub struct FixedTimestepSystem;
+
+impl<'s> System<'s> for FixedTimestepSystem {
+    type SystemData = (
+        Read<'s, Time>,
+        Write<'s, FixedTimestep>,
+    );
+
+    fn run(&mut self, (time, mut timestep): Self::SystemData) {
+        timestep.advance(time.delta_seconds());
+    }
+}
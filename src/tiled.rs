@@ -0,0 +1,413 @@
+//! Importer for maps made in [Tiled](https://www.mapeditor.org/), either saved as `.tmx` or exported
+//! as JSON.
+//!
+//! Tile layers become sprite entities, and unless a layer sets the custom boolean property
+//! `collision` to `false`, each horizontal run of tiles also becomes a collision box. Rectangles
+//! in object layers become collision boxes, except for an object named or typed `spawn`, which
+//! marks where the player is created. Setting the boolean property `one_way` on a tile layer, or
+//! the type `one_way` on an object, makes a one-way platform instead of a solid box. Objects typed
+//! `slope_right` or `slope_left` become slopes running corner to corner across the rectangle, rising
+//! towards the right or the left, and objects typed `climbable` become areas the player can climb
+//! around in, such as ladders.
+//!
+//! Only tile and object layers are read. Any other kind of layer, such as an image or group layer, is
+//! rejected rather than silently left out.
+
+mod tmx;
+
+use std::{error, fmt, fs::File, io::{self, BufReader}, path::Path};
+
+use serde_derive::Deserialize;
+
+use crate::level::{Bounds, GroundSegment, Point, SlopeSegment};
+
+const FLIPPED_FLAGS_MASK: u32 = 0xE000_0000;
+
+#[derive(Deserialize, Debug)]
+struct MapData {
+    width: u32,
+    height: u32,
+    tilewidth: u32,
+    tileheight: u32,
+    /// Parsed one at a time by `parse_layer`, so unsupported kinds of layer can be reported by name.
+    layers: Vec<serde_json::Value>,
+    tilesets: Vec<TilesetData>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum LayerData {
+    TileLayer {
+        name: String,
+        width: u32,
+        height: u32,
+        data: Vec<u32>,
+        #[serde(default)]
+        properties: Vec<PropertyData>,
+    },
+    ObjectGroup {
+        name: String,
+        objects: Vec<ObjectData>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+struct PropertyData {
+    name: String,
+    value: serde_json::Value,
+}
+
+#[derive(Deserialize, Debug)]
+struct ObjectData {
+    name: String,
+    #[serde(rename = "type", default)]
+    kind: String,
+    x: f32,
+    y: f32,
+    #[serde(default)]
+    width: f32,
+    #[serde(default)]
+    height: f32,
+}
+
+#[derive(Deserialize, Debug)]
+struct TilesetData {
+    firstgid: u32,
+    image: Option<String>,
+    imagewidth: Option<u32>,
+    imageheight: Option<u32>,
+    tilewidth: Option<u32>,
+    tileheight: Option<u32>,
+    columns: Option<u32>,
+    tilecount: Option<u32>,
+    #[serde(default)]
+    margin: u32,
+    #[serde(default)]
+    spacing: u32,
+    source: Option<String>,
+}
+
+/// A tileset image, ready to be cut into a sprite sheet.
+#[derive(Clone, Debug)]
+pub struct Tileset {
+    pub image_path: String,
+    pub image_w: u32,
+    pub image_h: u32,
+    pub tile_w: u32,
+    pub tile_h: u32,
+    pub columns: u32,
+    pub tile_count: u32,
+    pub margin: u32,
+    pub spacing: u32,
+}
+
+impl Tileset {
+    /// Pixel offset of the top left corner of the given tile within the tileset image.
+    pub fn tile_offset(&self, sprite_number: usize) -> (u32, u32) {
+        let index = sprite_number as u32;
+        let column = index % self.columns;
+        let row = index / self.columns;
+
+        (
+            self.margin + column * (self.tile_w + self.spacing),
+            self.margin + row * (self.tile_h + self.spacing),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tile {
+    pub tileset: usize,
+    pub sprite_number: usize,
+    pub left: f32,
+    pub bottom: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Collider {
+    pub left: f32,
+    pub bottom: f32,
+    pub width: f32,
+    pub height: f32,
+    pub one_way: bool,
+}
+
+/// A Tiled map converted to world coordinates, with y pointing up.
+#[derive(Clone, Debug)]
+pub struct TiledMap {
+    pub tilesets: Vec<Tileset>,
+    pub tiles: Vec<Tile>,
+    pub colliders: Vec<Collider>,
+    pub slopes: Vec<SlopeSegment>,
+    pub climbables: Vec<GroundSegment>,
+    pub player_spawn: Point,
+    pub bounds: Bounds,
+}
+
+#[derive(Debug)]
+pub enum TiledError {
+    Io(io::Error),
+    Parse(serde_json::Error),
+    Xml(xml::reader::Error),
+    NotTmx,
+    UnsupportedEncoding { layer: String, encoding: String },
+    InvalidTileData { layer: String },
+    InvalidTileSize { width: u32, height: u32 },
+    MapTooLarge { width: u32, height: u32 },
+    UnsupportedLayer { layer: String, kind: String },
+    InvalidLayerSize { layer: String, width: u32, height: u32, tiles: usize },
+    ExternalTileset { source: String },
+    MissingTilesetField { firstgid: u32, field: &'static str },
+    InvalidTilesetField { firstgid: u32, field: &'static str },
+    UnknownTile { layer: String, gid: u32 },
+    InvalidObject { layer: String, name: String },
+    MissingSpawn,
+}
+
+impl fmt::Display for TiledError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TiledError::Io(e) => write!(f, "failed to read map file: {}", e),
+            TiledError::Parse(e) => write!(f, "failed to parse map file: {}", e),
+            TiledError::Xml(e) => write!(f, "failed to parse TMX map: {}", e),
+            TiledError::NotTmx => write!(f, "map file is XML, but not a TMX map"),
+            TiledError::UnsupportedEncoding { layer, encoding } =>
+                write!(f, "tile layer {} is stored as {}, only CSV and XML tile data are supported", layer, encoding),
+            TiledError::InvalidTileData { layer } => write!(f, "tile layer {} has a tile which isn't a gid", layer),
+            TiledError::InvalidTileSize { width, height } =>
+                write!(f, "map tiles are {}x{}, they must be at least one pixel each way", width, height),
+            TiledError::MapTooLarge { width, height } => write!(f, "map of {}x{} tiles is too large", width, height),
+            TiledError::UnsupportedLayer { layer, kind } =>
+                write!(f, "layer {} is a {:?} layer, only tile layers and object layers are supported", layer, kind),
+            TiledError::InvalidLayerSize { layer, width, height, tiles } =>
+                write!(f, "tile layer {} is {}x{} tiles but has {}, it must be at least one tile wide and full", layer, width, height, tiles),
+            TiledError::ExternalTileset { source } =>
+                write!(f, "external tileset {} is not supported, embed it in the map instead", source),
+            TiledError::MissingTilesetField { firstgid, field } =>
+                write!(f, "tileset starting at gid {} is missing `{}`", firstgid, field),
+            TiledError::InvalidTilesetField { firstgid, field } =>
+                write!(f, "tileset starting at gid {} must have a `{}` greater than zero", firstgid, field),
+            TiledError::UnknownTile { layer, gid } =>
+                write!(f, "layer {} uses gid {} which is not in any tileset", layer, gid),
+            TiledError::InvalidObject { layer, name } =>
+                write!(f, "object {:?} in layer {} must have a positive width and height", name, layer),
+            TiledError::MissingSpawn => write!(f, "map must contain an object named or typed `spawn`"),
+        }
+    }
+}
+
+impl error::Error for TiledError {}
+
+impl From<io::Error> for TiledError {
+    fn from(e: io::Error) -> Self {
+        TiledError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for TiledError {
+    fn from(e: serde_json::Error) -> Self {
+        TiledError::Parse(e)
+    }
+}
+
+impl From<xml::reader::Error> for TiledError {
+    fn from(e: xml::reader::Error) -> Self {
+        TiledError::Xml(e)
+    }
+}
+
+impl TiledMap {
+    pub fn load(path: &str) -> Result<Self, TiledError> {
+        let is_tmx = Path::new(path).extension().is_some_and(|extension| extension.eq_ignore_ascii_case("tmx"));
+        let map: MapData = if is_tmx {
+            serde_json::from_value(tmx::read(BufReader::new(File::open(path)?))?)?
+        } else {
+            serde_json::from_reader(File::open(path)?)?
+        };
+        let map_dir = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
+
+        let tilesets = map.tilesets.iter()
+            .map(|tileset| convert_tileset(tileset, map_dir))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if map.tilewidth == 0 || map.tileheight == 0 {
+            return Err(TiledError::InvalidTileSize { width: map.tilewidth, height: map.tileheight });
+        }
+        let too_large = || TiledError::MapTooLarge { width: map.width, height: map.height };
+        let map_w = map.width.checked_mul(map.tilewidth).ok_or_else(too_large)? as f32;
+        let map_h = map.height.checked_mul(map.tileheight).ok_or_else(too_large)? as f32;
+        let tile_w = map.tilewidth as f32;
+        let tile_h = map.tileheight as f32;
+
+        let mut tiles = vec![];
+        let mut colliders = vec![];
+        let mut slopes = vec![];
+        let mut climbables = vec![];
+        let mut player_spawn = None;
+
+        let layers = map.layers.into_iter()
+            .map(parse_layer)
+            .collect::<Result<Vec<_>, _>>()?;
+        for layer in &layers {
+            match layer {
+                LayerData::TileLayer { name, width, height, data, properties } => {
+                    // chunking the data into rows needs a width, and rows which all line up
+                    if *width == 0 || (*width as usize).checked_mul(*height as usize) != Some(data.len()) {
+                        return Err(TiledError::InvalidLayerSize { layer: name.clone(), width: *width, height: *height, tiles: data.len() });
+                    }
+                    let collision = bool_property(properties, "collision").unwrap_or(true);
+                    let one_way = bool_property(properties, "one_way").unwrap_or(false);
+
+                    for (row_index, row) in data.chunks(*width as usize).enumerate() {
+                        let bottom = map_h - (row_index + 1) as f32 * tile_h;
+                        // start column of the run of solid tiles we are currently in, if any
+                        let mut run_start = None;
+
+                        for (column, &raw_gid) in row.iter().enumerate() {
+                            let gid = raw_gid & !FLIPPED_FLAGS_MASK;
+                            if gid == 0 {
+                                if let Some(start) = run_start.take() {
+                                    colliders.push(row_collider(start, column, bottom, tile_w, tile_h, one_way));
+                                }
+                                continue;
+                            }
+
+                            let (tileset, sprite_number) = find_tile(&map.tilesets, &tilesets, gid)
+                                .ok_or_else(|| TiledError::UnknownTile { layer: name.clone(), gid })?;
+                            tiles.push(Tile {
+                                tileset,
+                                sprite_number,
+                                left: column as f32 * tile_w,
+                                bottom,
+                            });
+
+                            if collision && run_start.is_none() {
+                                run_start = Some(column);
+                            }
+                        }
+                        if let Some(start) = run_start {
+                            colliders.push(row_collider(start, row.len(), bottom, tile_w, tile_h, one_way));
+                        }
+                    }
+                },
+                LayerData::ObjectGroup { name, objects } => {
+                    for object in objects {
+                        if object.name == "spawn" || object.kind == "spawn" {
+                            player_spawn = Some(Point {
+                                x: object.x + object.width / 2.,
+                                y: map_h - object.y - object.height / 2.,
+                            });
+                        } else if object.width > 0. && object.height > 0.
+                            && (object.kind == "slope_right" || object.kind == "slope_left") {
+                            let rising_right = object.kind == "slope_right";
+                            slopes.push(SlopeSegment {
+                                left: object.x,
+                                bottom: map_h - object.y - object.height,
+                                width: object.width,
+                                left_height: if rising_right { 0. } else { object.height },
+                                right_height: if rising_right { object.height } else { 0. },
+                            });
+                        } else if object.width > 0. && object.height > 0. && object.kind == "climbable" {
+                            climbables.push(GroundSegment {
+                                left: object.x,
+                                top: map_h - object.y,
+                                width: object.width,
+                                height: object.height,
+                            });
+                        } else if object.width > 0. && object.height > 0. {
+                            colliders.push(Collider {
+                                left: object.x,
+                                bottom: map_h - object.y - object.height,
+                                width: object.width,
+                                height: object.height,
+                                one_way: object.kind == "one_way",
+                            });
+                        } else {
+                            return Err(TiledError::InvalidObject { layer: name.clone(), name: object.name.clone() });
+                        }
+                    }
+                },
+            }
+        }
+
+        Ok(TiledMap {
+            tilesets,
+            tiles,
+            colliders,
+            slopes,
+            climbables,
+            player_spawn: player_spawn.ok_or(TiledError::MissingSpawn)?,
+            bounds: Bounds {
+                left: 0.,
+                right: map_w,
+                bottom: 0.,
+                top: map_h,
+            },
+        })
+    }
+}
+
+fn parse_layer(value: serde_json::Value) -> Result<LayerData, TiledError> {
+    let kind = value.get("type").and_then(|kind| kind.as_str()).unwrap_or_default();
+    if kind != "tilelayer" && kind != "objectgroup" {
+        let name = value.get("name").and_then(|name| name.as_str()).unwrap_or_default();
+        return Err(TiledError::UnsupportedLayer { layer: name.to_string(), kind: kind.to_string() });
+    }
+
+    Ok(serde_json::from_value(value)?)
+}
+
+fn convert_tileset(tileset: &TilesetData, map_dir: &Path) -> Result<Tileset, TiledError> {
+    if let Some(source) = &tileset.source {
+        return Err(TiledError::ExternalTileset { source: source.clone() });
+    }
+
+    let missing = |field| TiledError::MissingTilesetField { firstgid: tileset.firstgid, field };
+    // tile offsets are found by dividing by the columns, and sprites are cut out by the tile size
+    let positive = |field, value: Option<u32>| match value {
+        Some(0) => Err(TiledError::InvalidTilesetField { firstgid: tileset.firstgid, field }),
+        Some(value) => Ok(value),
+        None => Err(missing(field)),
+    };
+    let image = tileset.image.as_ref().ok_or_else(|| missing("image"))?;
+
+    Ok(Tileset {
+        image_path: map_dir.join(image).to_string_lossy().into_owned(),
+        image_w: tileset.imagewidth.ok_or_else(|| missing("imagewidth"))?,
+        image_h: tileset.imageheight.ok_or_else(|| missing("imageheight"))?,
+        tile_w: positive("tilewidth", tileset.tilewidth)?,
+        tile_h: positive("tileheight", tileset.tileheight)?,
+        columns: positive("columns", tileset.columns)?,
+        tile_count: tileset.tilecount.ok_or_else(|| missing("tilecount"))?,
+        margin: tileset.margin,
+        spacing: tileset.spacing,
+    })
+}
+
+fn find_tile(tileset_data: &[TilesetData], tilesets: &[Tileset], gid: u32) -> Option<(usize, usize)> {
+    // tilesets are sorted by firstgid, so the last one starting at or before the gid owns it
+    let index = tileset_data.iter().rposition(|tileset| tileset.firstgid <= gid)?;
+    let local_id = gid - tileset_data[index].firstgid;
+
+    if local_id < tilesets[index].tile_count {
+        Some((index, local_id as usize))
+    } else {
+        None
+    }
+}
+
+fn bool_property(properties: &[PropertyData], name: &str) -> Option<bool> {
+    properties.iter()
+        .find(|property| property.name == name)
+        .and_then(|property| property.value.as_bool())
+}
+
+fn row_collider(start: usize, end: usize, bottom: f32, tile_w: f32, tile_h: f32, one_way: bool) -> Collider {
+    Collider {
+        left: start as f32 * tile_w,
+        bottom,
+        width: (end - start) as f32 * tile_w,
+        height: tile_h,
+        one_way,
+    }
+}
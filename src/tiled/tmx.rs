@@ -0,0 +1,207 @@
+//! Reads maps saved in Tiled's own TMX format into the layout of its JSON export, so both are loaded
+//! and checked the same way.
+//!
+//! Tile layer data has to be stored as CSV, or as the older one `<tile>` element per tile. Base64 data
+//! is rejected.
+
+use std::io::Read;
+
+use serde_json::{Map, Value};
+use xml::reader::{EventReader, XmlEvent};
+
+use super::TiledError;
+
+/// An XML element along with everything inside it.
+struct Element {
+    name: String,
+    attributes: Vec<(String, String)>,
+    children: Vec<Element>,
+    text: String,
+}
+
+impl Element {
+    fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes.iter()
+            .find(|(attribute, _)| attribute == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    fn child(&self, name: &str) -> Option<&Element> {
+        self.children.iter().find(|child| child.name == name)
+    }
+
+    fn children<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Element> + 'a {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+}
+
+/// The map as it would have been exported to JSON.
+pub fn read<R: Read>(reader: R) -> Result<Value, TiledError> {
+    let map = parse(reader)?;
+    if map.name != "map" {
+        return Err(TiledError::NotTmx);
+    }
+
+    let mut json = Map::new();
+    for &name in ["width", "height", "tilewidth", "tileheight"].iter() {
+        copy_number(&mut json, &map, name, name);
+    }
+    json.insert("tilesets".to_string(), map.children("tileset").map(tileset).collect());
+    let layers = map.children.iter()
+        .filter(|child| !matches!(child.name.as_str(), "tileset" | "properties" | "editorsettings"))
+        .map(layer)
+        .collect::<Result<Vec<_>, _>>()?;
+    json.insert("layers".to_string(), Value::Array(layers));
+
+    Ok(Value::Object(json))
+}
+
+fn parse<R: Read>(reader: R) -> Result<Element, TiledError> {
+    let mut open: Vec<Element> = vec![];
+    for event in EventReader::new(reader) {
+        match event? {
+            XmlEvent::StartElement { name, attributes, .. } => open.push(Element {
+                name: name.local_name,
+                attributes: attributes.into_iter()
+                    .map(|attribute| (attribute.name.local_name, attribute.value))
+                    .collect(),
+                children: vec![],
+                text: String::new(),
+            }),
+            XmlEvent::EndElement { .. } => {
+                let element = open.pop().expect("end of an element which was never started");
+                match open.last_mut() {
+                    Some(parent) => parent.children.push(element),
+                    None => return Ok(element),
+                }
+            },
+            XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                if let Some(element) = open.last_mut() {
+                    element.text.push_str(&text);
+                }
+            },
+            _ => {},
+        }
+    }
+
+    // the reader reports a document without a root element as an error, so this isn't reached
+    Err(TiledError::NotTmx)
+}
+
+fn tileset(element: &Element) -> Value {
+    let mut json = Map::new();
+    for &name in ["firstgid", "tilewidth", "tileheight", "columns", "tilecount", "margin", "spacing"].iter() {
+        copy_number(&mut json, element, name, name);
+    }
+    copy_text(&mut json, element, "source", "source");
+    if let Some(image) = element.child("image") {
+        copy_text(&mut json, image, "source", "image");
+        copy_number(&mut json, image, "width", "imagewidth");
+        copy_number(&mut json, image, "height", "imageheight");
+    }
+    Value::Object(json)
+}
+
+fn layer(element: &Element) -> Result<Value, TiledError> {
+    let name = element.attribute("name").unwrap_or_default();
+    let mut json = Map::new();
+    json.insert("name".to_string(), Value::from(name));
+
+    match element.name.as_str() {
+        "layer" => {
+            json.insert("type".to_string(), Value::from("tilelayer"));
+            copy_number(&mut json, element, "width", "width");
+            copy_number(&mut json, element, "height", "height");
+            json.insert("data".to_string(), Value::from(tile_data(element, name)?));
+            json.insert("properties".to_string(), properties(element));
+        },
+        "objectgroup" => {
+            json.insert("type".to_string(), Value::from("objectgroup"));
+            json.insert("objects".to_string(), element.children("object").map(object).collect());
+        },
+        // image layers, groups and anything newer keep their own name, and are turned away by `parse_layer`
+        kind => {
+            json.insert("type".to_string(), Value::from(kind));
+        },
+    }
+
+    Ok(Value::Object(json))
+}
+
+fn tile_data(layer: &Element, name: &str) -> Result<Vec<u32>, TiledError> {
+    // a layer without data is left empty, to be rejected for its size
+    let data = match layer.child("data") {
+        Some(data) => data,
+        None => return Ok(vec![]),
+    };
+    let invalid = || TiledError::InvalidTileData { layer: name.to_string() };
+
+    match data.attribute("encoding") {
+        Some("csv") => data.text.split(',')
+            .map(|gid| gid.trim().parse().map_err(|_| invalid()))
+            .collect(),
+        None => data.children("tile")
+            .map(|tile| tile.attribute("gid").map_or(Ok(0), |gid| gid.parse().map_err(|_| invalid())))
+            .collect(),
+        Some(encoding) => Err(TiledError::UnsupportedEncoding { layer: name.to_string(), encoding: encoding.to_string() }),
+    }
+}
+
+/// Custom properties, with their values typed the way the JSON export types them.
+fn properties(element: &Element) -> Value {
+    let properties = match element.child("properties") {
+        Some(properties) => properties,
+        None => return Value::Array(vec![]),
+    };
+
+    properties.children("property")
+        .map(|property| {
+            let value = property.attribute("value").unwrap_or_default();
+            let value = match property.attribute("type") {
+                Some("bool") => Value::from(value == "true"),
+                Some("int") | Some("float") => number(value),
+                _ => Value::from(value),
+            };
+            let mut json = Map::new();
+            json.insert("name".to_string(), Value::from(property.attribute("name").unwrap_or_default()));
+            json.insert("value".to_string(), value);
+            Value::Object(json)
+        })
+        .collect()
+}
+
+fn object(element: &Element) -> Value {
+    // TMX leaves out empty names and types, and zero sizes. newer versions of Tiled call the type a class
+    let mut json = Map::new();
+    json.insert("name".to_string(), Value::from(element.attribute("name").unwrap_or_default()));
+    let kind = element.attribute("type").or_else(|| element.attribute("class")).unwrap_or_default();
+    json.insert("type".to_string(), Value::from(kind));
+    for &name in ["x", "y", "width", "height"].iter() {
+        json.insert(name.to_string(), number(element.attribute(name).unwrap_or("0")));
+    }
+    Value::Object(json)
+}
+
+fn copy_text(json: &mut Map<String, Value>, element: &Element, attribute: &str, key: &str) {
+    if let Some(value) = element.attribute(attribute) {
+        json.insert(key.to_string(), Value::from(value));
+    }
+}
+
+fn copy_number(json: &mut Map<String, Value>, element: &Element, attribute: &str, key: &str) {
+    if let Some(value) = element.attribute(attribute) {
+        json.insert(key.to_string(), number(value));
+    }
+}
+
+/// The attribute as a JSON number. One which isn't a number is kept as text, so it fails to load with
+/// the same error it would in a JSON map.
+fn number(value: &str) -> Value {
+    if let Ok(integer) = value.parse::<u64>() {
+        Value::from(integer)
+    } else if let Ok(float) = value.parse::<f64>() {
+        Value::from(float)
+    } else {
+        Value::from(value)
+    }
+}
\ No newline at end of file
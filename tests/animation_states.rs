@@ -0,0 +1,61 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    components::PlayerState,
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    level::{GroundSegment, Level},
+    systems::{InputScript, PlayerInput},
+};
+
+use common::{floor, idle, jump, player, right, standing_at};
+
+const SETTLE_FRAMES: usize = 20;
+
+/// Ground at y = 100, with a ledge at y = 300 ending at x = 400 which the player starts on.
+fn ledge_level() -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![
+            floor(100.),
+            GroundSegment { left: -1000., top: 300., width: 1400., height: 200. },
+        ],
+        player_spawn: standing_at(100., 300.),
+        ..Level::default()
+    })
+}
+
+/// Each state the player goes through after settling onto the ledge and then holding `input`, in order,
+/// without repeats.
+fn states(input: PlayerInput, held_frames: usize, frames: usize) -> Vec<PlayerState> {
+    let script = InputScript::new().hold(idle(), SETTLE_FRAMES).hold(input, held_frames);
+    let mut simulation = Simulation::new(&ledge_level(), MovementConfig::default(), script);
+    simulation.step(SETTLE_FRAMES);
+
+    let mut states: Vec<PlayerState> = vec![];
+    for _ in 0..frames {
+        simulation.step(1);
+        let state = player(&simulation).state;
+        if states.last() != Some(&state) {
+            states.push(state);
+        }
+    }
+    states
+}
+
+#[test]
+fn a_jump_rises_tops_out_falls_and_lands() {
+    // let go before landing, so the player doesn't jump again
+    assert_eq!(
+        states(jump(), 40, 120),
+        vec![PlayerState::Rising, PlayerState::Apex, PlayerState::Falling, PlayerState::Landing, PlayerState::Idle],
+    );
+}
+
+#[test]
+fn walking_off_a_ledge_falls_without_topping_out() {
+    let states = states(right(), 150, 150);
+
+    assert!(states.contains(&PlayerState::Falling) && states.contains(&PlayerState::Landing), "went through {:?}", states);
+    assert!(!states.contains(&PlayerState::Rising) && !states.contains(&PlayerState::Apex), "went through {:?}", states);
+}
\ No newline at end of file
@@ -0,0 +1,64 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    collision::CONTACT_EPSILON,
+    components::PlayerState,
+    entities::LevelSource,
+    level::{GroundSegment, Level},
+    systems::InputScript,
+};
+
+use common::{floor, jump, moving, run, standing_at};
+
+const GROUND_TOP: f32 = 100.;
+const PLATFORM_TOP: f32 = 500.;
+
+/// A ladder from the ground up to a one-way platform, the player standing at the foot or the top of it.
+fn ladder_level(spawn_top: f32) -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![floor(GROUND_TOP)],
+        one_way_platforms: vec![GroundSegment { left: 0., top: PLATFORM_TOP, width: 400., height: 20. }],
+        climbables: vec![GroundSegment { left: 150., top: PLATFORM_TOP, width: 100., height: PLATFORM_TOP - GROUND_TOP }],
+        player_spawn: standing_at(200., spawn_top),
+        ..Level::default()
+    })
+}
+
+fn climb(y: f32, frames: usize) -> InputScript {
+    InputScript::new().hold(moving(0., y), frames)
+}
+
+#[test]
+fn holding_up_climbs_to_the_top_of_a_ladder() {
+    let player = run(&ladder_level(GROUND_TOP), climb(1., 180), 180);
+    assert_eq!(player.state, PlayerState::Climbing);
+    assert!((player.body.bottom - PLATFORM_TOP).abs() <= CONTACT_EPSILON, "player stopped at {:?}", player);
+}
+
+#[test]
+fn letting_go_of_the_input_holds_on_without_falling() {
+    let level = ladder_level(GROUND_TOP);
+    let halfway = run(&level, climb(1., 30), 30);
+    let later = run(&level, climb(1., 30), 90);
+
+    assert_eq!(later.state, PlayerState::Climbing);
+    assert_eq!(later.body.bottom, halfway.body.bottom);
+}
+
+#[test]
+fn jumping_off_a_ladder_lets_go() {
+    let script = climb(1., 180)
+        .hold(jump(), 1);
+
+    // lands back on the platform at the top of the ladder
+    let player = run(&ladder_level(GROUND_TOP), script, 300);
+    assert_eq!(player.state, PlayerState::Idle);
+    assert!((player.body.bottom - PLATFORM_TOP).abs() <= CONTACT_EPSILON, "player landed at {:?}", player);
+}
+
+#[test]
+fn holding_down_climbs_down_through_a_platform_to_the_ground() {
+    let player = run(&ladder_level(PLATFORM_TOP), climb(-1., 180), 180);
+    assert_eq!(player.state, PlayerState::Idle);
+    assert!((player.body.bottom - GROUND_TOP).abs() <= CONTACT_EPSILON, "player stopped at {:?}", player);
+}
\ No newline at end of file
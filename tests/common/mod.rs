@@ -0,0 +1,98 @@
+//! Helpers shared by the integration tests. Each test file only uses some of them.
+#![allow(dead_code)]
+
+use amethyst::{
+    ecs::Entity,
+    prelude::*,
+};
+use amethyst_2d_platformer_demo::{
+    PLAYER_H,
+    components::{RigidBody, TwoDimObject, Velocity},
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::{PlayerSnapshot, Simulation},
+    level::{GroundSegment, Level, Point},
+    systems::{InputScript, PlayerInput},
+};
+
+pub fn idle() -> PlayerInput {
+    PlayerInput::default()
+}
+
+pub fn jump() -> PlayerInput {
+    PlayerInput { jump: true, ..PlayerInput::default() }
+}
+
+pub fn right() -> PlayerInput {
+    PlayerInput { x: 1., ..PlayerInput::default() }
+}
+
+pub fn right_jump() -> PlayerInput {
+    PlayerInput { jump: true, ..right() }
+}
+
+pub fn dash() -> PlayerInput {
+    PlayerInput { dash: true, ..PlayerInput::default() }
+}
+
+/// Holding the arrow keys, with up and right positive.
+pub fn moving(x: f32, y: f32) -> PlayerInput {
+    PlayerInput { x, y, ..PlayerInput::default() }
+}
+
+/// Where to spawn the player so they stand at `x` with their feet at `bottom`.
+pub fn standing_at(x: f32, bottom: f32) -> Point {
+    Point { x, y: bottom + PLAYER_H as f32 / 2. }
+}
+
+/// Flat ground from x = -1000 to 2000 with its top at `top`.
+pub fn floor(top: f32) -> GroundSegment {
+    GroundSegment { left: -1000., top, width: 3000., height: 100. }
+}
+
+/// Nothing but the floor, with the player standing on it at `spawn_x`. Tests add to it with struct
+/// update syntax.
+pub fn flat_level(ground_top: f32, spawn_x: f32) -> Level {
+    Level {
+        ground: vec![floor(ground_top)],
+        player_spawn: standing_at(spawn_x, ground_top),
+        ..Level::default()
+    }
+}
+
+pub fn player(simulation: &Simulation) -> PlayerSnapshot {
+    simulation.snapshot().players[0]
+}
+
+/// Where the player is after replaying the script for `frames` frames.
+pub fn run(level: &LevelSource, script: InputScript, frames: usize) -> PlayerSnapshot {
+    run_with(level, MovementConfig::default(), script, frames)
+}
+
+pub fn run_with(level: &LevelSource, movement: MovementConfig, script: InputScript, frames: usize) -> PlayerSnapshot {
+    let mut simulation = Simulation::new(level, movement, script);
+    simulation.step(frames);
+    player(&simulation)
+}
+
+/// A square rigid body, for the extra bodies a level file can't describe.
+pub fn add_body(simulation: &mut Simulation, size: f32, left: f32, bottom: f32, velocity: Velocity, rigid_body: RigidBody) -> Entity {
+    let mut two_dim_object = TwoDimObject::new(size, size);
+    two_dim_object.set_left(left);
+    two_dim_object.set_bottom(bottom);
+
+    simulation.world_mut().create_entity()
+        .with(two_dim_object)
+        .with(rigid_body)
+        .with(velocity)
+        .build()
+}
+
+/// A square body flying in a straight line, unaffected by gravity.
+pub fn add_projectile(simulation: &mut Simulation, size: f32, left: f32, bottom: f32, velocity: Velocity) -> Entity {
+    add_body(simulation, size, left, bottom, velocity, RigidBody { gravity_scale: 0., ..RigidBody::default() })
+}
+
+pub fn body(simulation: &Simulation, entity: Entity) -> TwoDimObject {
+    *simulation.world().read_storage::<TwoDimObject>().get(entity).unwrap()
+}
\ No newline at end of file
@@ -0,0 +1,79 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    collision::CONTACT_EPSILON,
+    components::{TwoDimObject, Velocity},
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    level::{Crate, Level},
+    systems::InputScript,
+};
+
+use common::{add_projectile, body, flat_level};
+
+const GROUND_TOP: f32 = 74.;
+/// Top of the lower crate beside the two stacked at the left edge of the level.
+const STEP_TOP: f32 = 151.;
+const STEP_RIGHT: f32 = 154.;
+const ROW_LEFT: f32 = 769.;
+const ROW_BOTTOM: f32 = 248.;
+const ROW_TOP: f32 = 325.;
+
+/// The crate stack and floating crate row from `level.ron`, with the player parked out of the way.
+fn crates_level() -> LevelSource {
+    let solid = |left, bottom| Crate { left, bottom, pushable: false };
+    LevelSource::Level(Level {
+        crates: vec![
+            solid(0., 74.), solid(77., 74.), solid(0., 151.),
+            solid(923., 248.), solid(846., 248.), solid(769., 248.),
+        ],
+        ..flat_level(GROUND_TOP, 1800.)
+    })
+}
+
+fn fly(left: f32, bottom: f32, velocity: Velocity, frames: usize) -> TwoDimObject {
+    let mut simulation = Simulation::new(&crates_level(), MovementConfig::default(), InputScript::new());
+    let projectile = add_projectile(&mut simulation, 20., left, bottom, velocity);
+    simulation.step(frames);
+    body(&simulation, projectile)
+}
+
+#[test]
+fn diving_past_the_corner_of_the_stack_hits_its_side() {
+    // reaches the side of the lower crate just after dropping below its top, within a single step.
+    // moving along x before y would have put the box on top of the crate instead
+    let body = fly(200., STEP_TOP + 9., Velocity { x: -3000., y: -600. }, 30);
+    assert!((body.left() - STEP_RIGHT).abs() <= CONTACT_EPSILON, "box ended at {:?}", body);
+    assert!((body.bottom() - GROUND_TOP).abs() <= CONTACT_EPSILON, "box ended at {:?}", body);
+}
+
+#[test]
+fn flying_into_the_inner_corner_of_the_stack_touches_both_sides() {
+    let body = fly(120., 200., Velocity { x: -600., y: -600. }, 30);
+    assert!((body.left() - 77.).abs() <= CONTACT_EPSILON, "box ended at {:?}", body);
+    assert!((body.bottom() - STEP_TOP).abs() <= CONTACT_EPSILON, "box ended at {:?}", body);
+    assert!(body.contacts.left.is_some() && body.contacts.on_ground());
+}
+
+#[test]
+fn rising_past_the_corner_of_the_floating_row_hits_its_underside() {
+    // clears the end of the row by the end of the first step, but is under it when reaching its height
+    let body = fly(ROW_LEFT - 11., ROW_BOTTOM - 28., Velocity { x: -600., y: 600. }, 60);
+    assert!((body.top() - ROW_BOTTOM).abs() <= CONTACT_EPSILON, "box ended at {:?}", body);
+    assert!(body.right() < ROW_LEFT);
+}
+
+#[test]
+fn sliding_along_the_floating_row_doesnt_catch_on_the_seams_between_crates() {
+    let body = fly(ROW_LEFT + 6., ROW_TOP, Velocity { x: 600., y: 0. }, 15);
+    assert!((body.left() - (ROW_LEFT + 156.)).abs() <= CONTACT_EPSILON, "box ended at {:?}", body);
+    assert_eq!(body.bottom(), ROW_TOP);
+}
+
+#[test]
+fn a_box_too_fast_to_sub_step_still_stops_at_the_stack() {
+    // several times the width of a crate per step, more than the sub-steps can break up
+    let body = fly(1000., 100., Velocity { x: -60000., y: 0. }, 5);
+    assert!((body.left() - STEP_RIGHT).abs() <= CONTACT_EPSILON, "box ended at {:?}", body);
+}
\ No newline at end of file
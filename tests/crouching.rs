@@ -0,0 +1,61 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    PLAYER_H,
+    collision::CONTACT_EPSILON,
+    components::PlayerState,
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::PlayerSnapshot,
+    level::{GroundSegment, Level},
+    systems::InputScript,
+};
+
+use common::{flat_level, floor, moving};
+
+const GROUND_TOP: f32 = 100.;
+const CEILING_LEFT: f32 = 300.;
+const CEILING_RIGHT: f32 = 700.;
+
+/// Flat ground with a low ceiling over part of it, too low to stand under but high enough to crouch.
+fn low_ceiling_level() -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![
+            floor(GROUND_TOP),
+            GroundSegment { left: CEILING_LEFT, top: GROUND_TOP + 180., width: CEILING_RIGHT - CEILING_LEFT, height: 50. },
+        ],
+        ..flat_level(GROUND_TOP, 100.)
+    })
+}
+
+fn run(script: InputScript, frames: usize) -> PlayerSnapshot {
+    common::run(&low_ceiling_level(), script, frames)
+}
+
+#[test]
+fn holding_down_crouches_with_the_feet_in_place() {
+    let player = run(InputScript::new().hold(moving(0., -1.), 30), 30);
+    assert_eq!(player.state, PlayerState::Crouching);
+    assert!((player.body.top - player.body.bottom - MovementConfig::default().crouch_height).abs() <= CONTACT_EPSILON);
+    assert!((player.body.bottom - GROUND_TOP).abs() <= CONTACT_EPSILON, "player sank to {:?}", player);
+}
+
+#[test]
+fn letting_go_of_down_stands_back_up() {
+    let player = run(InputScript::new().hold(moving(0., -1.), 30), 60);
+    assert_eq!(player.state, PlayerState::Idle);
+    assert!((player.body.top - player.body.bottom - PLAYER_H as f32).abs() <= CONTACT_EPSILON);
+}
+
+#[test]
+fn stays_crouched_under_a_ceiling_until_there_is_room_to_stand() {
+    let script = InputScript::new().hold(moving(1., -1.), 150);
+    let under_ceiling = run(script.clone(), 180);
+    assert_eq!(under_ceiling.state, PlayerState::Crouching);
+    assert!(under_ceiling.body.right > CEILING_LEFT, "player only got to {:?}", under_ceiling);
+
+    // walking on without holding down stands up once past the ceiling
+    let past_ceiling = run(script.hold(moving(0., 0.), 30).hold(moving(1., 0.), 360), 540);
+    assert!(past_ceiling.body.left > CEILING_RIGHT, "player only got to {:?}", past_ceiling);
+    assert!((past_ceiling.body.top - past_ceiling.body.bottom - PLAYER_H as f32).abs() <= CONTACT_EPSILON);
+}
\ No newline at end of file
@@ -0,0 +1,73 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    CRATE_SIZE,
+    collision::CONTACT_EPSILON,
+    components::PlayerState,
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::PlayerSnapshot,
+    level::{Crate, Level},
+    systems::InputScript,
+};
+
+use common::{dash, flat_level, idle, jump, run_with};
+
+const GROUND_TOP: f32 = 100.;
+const SPAWN_X: f32 = 100.;
+
+/// The player standing on flat ground at x = `SPAWN_X`, facing right.
+fn level(crates: Vec<Crate>) -> LevelSource {
+    LevelSource::Level(Level { crates, ..flat_level(GROUND_TOP, SPAWN_X) })
+}
+
+fn center_x(player: &PlayerSnapshot) -> f32 {
+    (player.body.left + player.body.right) / 2.
+}
+
+#[test]
+fn dashing_covers_the_dash_distance() {
+    let movement = MovementConfig::default();
+    let script = InputScript::new().hold(idle(), 10).hold(dash(), 1);
+
+    let dashing = run_with(&level(vec![]), movement, script.clone(), 15);
+    assert_eq!(dashing.state, PlayerState::Dashing);
+
+    let player = run_with(&level(vec![]), movement, script, 60);
+    assert!((center_x(&player) - SPAWN_X - movement.dash_distance).abs() <= CONTACT_EPSILON, "player ended at {:?}", player);
+    assert_eq!(player.state, PlayerState::Idle);
+}
+
+#[test]
+fn only_one_air_dash_until_landing() {
+    let movement = MovementConfig { dash_cooldown_frames: 0, ..MovementConfig::default() };
+    let script = InputScript::new()
+        .hold(idle(), 10)
+        .hold(jump(), 1)
+        .hold(idle(), 4)
+        .hold(dash(), 1)
+        .hold(idle(), 14)
+        .hold(dash(), 1);
+
+    let after_air_dashes = run_with(&level(vec![]), movement, script.clone(), 120);
+    assert!(after_air_dashes.body.bottom - GROUND_TOP <= CONTACT_EPSILON, "player still in the air at {:?}", after_air_dashes);
+    assert!((center_x(&after_air_dashes) - SPAWN_X - movement.dash_distance).abs() <= CONTACT_EPSILON);
+
+    // landing gives the dash back
+    let script = script.hold(idle(), 89).hold(dash(), 1);
+    let after_landing = run_with(&level(vec![]), movement, script, 180);
+    assert!((center_x(&after_landing) - SPAWN_X - 2. * movement.dash_distance).abs() <= CONTACT_EPSILON);
+}
+
+#[test]
+fn a_fast_dash_stops_at_a_crate_instead_of_passing_through() {
+    // far enough in one step to jump clean over the crate without sweeping
+    let movement = MovementConfig { dash_distance: 2000., dash_duration_frames: 2, ..MovementConfig::default() };
+    let crate_left = 400.;
+    let level = level(vec![Crate { left: crate_left, bottom: GROUND_TOP, pushable: false }]);
+    let script = InputScript::new().hold(idle(), 10).hold(dash(), 1);
+
+    let player = run_with(&level, movement, script, 60);
+    assert!((player.body.right - crate_left).abs() <= CONTACT_EPSILON, "player ended at {:?}", player);
+    assert!(movement.dash_distance / 2. > crate_left + CRATE_SIZE - SPAWN_X);
+}
\ No newline at end of file
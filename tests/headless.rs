@@ -0,0 +1,31 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    collision::CONTACT_EPSILON,
+    entities::LevelSource,
+    level::{Crate, Level},
+    systems::InputScript,
+};
+
+use common::{flat_level, right, run};
+
+const GROUND_TOP: f32 = 74.;
+const STACK_LEFT: f32 = 600.;
+
+/// The crate stack from the left edge of `level.ron`, moved to the right of the player.
+fn crate_stack_level() -> LevelSource {
+    let solid = |left, bottom| Crate { left, bottom, pushable: false };
+    LevelSource::Level(Level {
+        crates: vec![
+            solid(STACK_LEFT, GROUND_TOP), solid(STACK_LEFT + 77., GROUND_TOP), solid(STACK_LEFT, GROUND_TOP + 77.),
+        ],
+        ..flat_level(GROUND_TOP, 300.)
+    })
+}
+
+#[test]
+fn holding_right_stops_at_the_left_edge_of_the_crate_stack() {
+    let player = run(&crate_stack_level(), InputScript::new().hold(right(), 120), 120);
+    assert!((player.body.right - STACK_LEFT).abs() <= CONTACT_EPSILON, "player ended at {:?}", player);
+    assert!((player.body.bottom - GROUND_TOP).abs() <= CONTACT_EPSILON, "player ended at {:?}", player);
+}
\ No newline at end of file
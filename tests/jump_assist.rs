@@ -0,0 +1,137 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    components::PlayerState,
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    level::{GroundSegment, Level, Point},
+    systems::InputScript,
+};
+
+use common::{floor, idle, jump, player, right, right_jump, run, run_with, standing_at};
+
+/// The player standing on a raised platform whose right edge is at x = 400.
+fn platform_level() -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![
+            floor(74.),
+            GroundSegment { left: 0., top: 300., width: 400., height: 50. },
+        ],
+        player_spawn: standing_at(300., 300.),
+        ..Level::default()
+    })
+}
+
+/// The player in the air, above the ground.
+fn falling_level() -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![floor(74.)],
+        player_spawn: Point { x: 500., y: 600. },
+        ..Level::default()
+    })
+}
+
+/// Frames until the player starts to fall after walking right off the platform.
+fn frames_until_off_platform() -> usize {
+    let mut simulation = Simulation::new(&platform_level(), MovementConfig::default(), InputScript::new().hold(right(), 600));
+    for frame in 1..600 {
+        simulation.step(1);
+        if player(&simulation).body.velocity_y < 0. {
+            return frame;
+        }
+    }
+    panic!("player never walked off the platform");
+}
+
+/// Frames until the player lands on the ground after falling from the spawn point.
+fn frames_until_landed() -> usize {
+    let mut simulation = Simulation::new(&falling_level(), MovementConfig::default(), InputScript::new());
+    let mut fallen = false;
+    for frame in 1..600 {
+        simulation.step(1);
+        let velocity_y = player(&simulation).body.velocity_y;
+        if velocity_y < 0. {
+            fallen = true;
+        } else if fallen && velocity_y == 0. {
+            return frame;
+        }
+    }
+    panic!("player never landed");
+}
+
+#[test]
+fn can_jump_just_after_walking_off_a_ledge() {
+    let off_platform = frames_until_off_platform();
+    let script = InputScript::new()
+        .hold(right(), off_platform + 1)
+        .hold(right_jump(), 1);
+
+    let player = run(&platform_level(), script, off_platform + 2);
+    assert!(player.body.velocity_y > 0., "expected a coyote time jump, got {:?}", player);
+}
+
+#[test]
+fn cannot_jump_once_coyote_time_has_passed() {
+    let off_platform = frames_until_off_platform();
+    // without air jumps, so a late press can't be turned into a double jump
+    let movement = MovementConfig { air_jumps: 0, ..MovementConfig::default() };
+    let coyote_time_frames = movement.coyote_time_frames as usize;
+    let script = InputScript::new()
+        .hold(right(), off_platform + coyote_time_frames + 1)
+        .hold(right_jump(), 1);
+
+    let player = run_with(&platform_level(), movement, script, off_platform + coyote_time_frames + 2);
+    assert!(player.body.velocity_y < 0., "expected the player to keep falling, got {:?}", player);
+}
+
+#[test]
+fn jump_pressed_just_before_landing_is_buffered() {
+    let landed = frames_until_landed();
+    let script = InputScript::new()
+        .hold(idle(), landed - 3)
+        .hold(jump(), 1);
+
+    let player = run(&falling_level(), script, landed + 1);
+    assert!(player.body.velocity_y > 0., "expected a buffered jump, got {:?}", player);
+}
+
+#[test]
+fn jump_pressed_long_before_landing_is_ignored() {
+    let landed = frames_until_landed();
+    let jump_buffer_frames = MovementConfig::default().jump_buffer_frames as usize;
+    let script = InputScript::new()
+        .hold(idle(), landed - jump_buffer_frames - 3)
+        .hold(jump(), 1);
+
+    let player = run(&falling_level(), script, landed + 1);
+    assert_eq!(player.body.velocity_y, 0., "expected the player to stay on the ground, got {:?}", player);
+}
+
+#[test]
+fn can_air_jump_after_leaving_the_ground() {
+    let off_platform = frames_until_off_platform();
+    let late = off_platform + MovementConfig::default().coyote_time_frames as usize + 10;
+    let script = InputScript::new()
+        .hold(right(), late)
+        .hold(right_jump(), 1);
+
+    let player = run(&platform_level(), script, late + 1);
+    assert!(player.body.velocity_y > 0., "expected an air jump, got {:?}", player);
+    assert_eq!(player.state, PlayerState::AirJumping);
+}
+
+#[test]
+fn air_jumps_run_out_until_landing() {
+    let off_platform = frames_until_off_platform();
+    let late = off_platform + MovementConfig::default().coyote_time_frames as usize + 10;
+    // air jump, then fall until well past the apex and press jump again
+    let script = InputScript::new()
+        .hold(right(), late)
+        .hold(right_jump(), 1)
+        .hold(right(), 25)
+        .hold(right_jump(), 1);
+
+    let player = run(&platform_level(), script, late + 27);
+    assert!(player.body.velocity_y < 0., "expected the second air jump to be refused, got {:?}", player);
+}
\ No newline at end of file
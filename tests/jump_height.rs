@@ -0,0 +1,56 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    PHYSICS_STEP_SECONDS,
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    systems::InputScript,
+};
+
+use common::{flat_level, idle, jump, player};
+
+const GROUND_TOP: f32 = 74.;
+
+/// How far above the ground the player's feet get after settling and then holding jump for
+/// `jump_frames` frames.
+fn peak_height(jump_frames: usize) -> f32 {
+    let level = LevelSource::Level(flat_level(GROUND_TOP, 500.));
+    let script = InputScript::new()
+        .hold(idle(), 10)
+        .hold(jump(), jump_frames);
+    let mut simulation = Simulation::new(&level, MovementConfig::default(), script);
+    simulation.step(10);
+
+    let mut peak = GROUND_TOP;
+    for _ in 0..120 {
+        simulation.step(1);
+        peak = peak.max(player(&simulation).body.bottom);
+    }
+    peak - GROUND_TOP
+}
+
+/// How far the player rises in the frame they jump, before releasing the button can cut the jump short.
+fn first_step() -> f32 {
+    MovementConfig::default().jump_velocity() * PHYSICS_STEP_SECONDS
+}
+
+#[test]
+fn tapping_jump_reaches_about_min_jump_height() {
+    let movement = MovementConfig::default();
+    let peak = peak_height(1);
+    assert!(
+        peak >= movement.min_jump_height && peak <= movement.min_jump_height + 2. * first_step(),
+        "tapped jump peaked at {}, expected about {}", peak, movement.min_jump_height,
+    );
+}
+
+#[test]
+fn holding_jump_reaches_about_max_jump_height() {
+    let movement = MovementConfig::default();
+    let peak = peak_height(120);
+    assert!(
+        (peak - movement.max_jump_height).abs() <= first_step(),
+        "held jump peaked at {}, expected about {}", peak, movement.max_jump_height,
+    );
+}
\ No newline at end of file
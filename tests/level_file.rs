@@ -0,0 +1,52 @@
+use std::{env, fs};
+
+use amethyst_2d_platformer_demo::level::{GroundSegment, Level, LevelError};
+
+const LEVEL_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/resources/level.ron");
+
+fn shipped_level() -> Level {
+    Level::load(LEVEL_PATH).expect("Failed to load the shipped level")
+}
+
+#[test]
+fn loads_the_shipped_level() {
+    let level = shipped_level();
+    assert_eq!(level.ground.len(), 1);
+    assert_eq!(level.crates.len(), 8);
+    assert_eq!((level.player_spawn.x, level.player_spawn.y), (500., 500.));
+}
+
+#[test]
+fn rejects_a_level_missing_a_required_field() {
+    let ron = fs::read_to_string(LEVEL_PATH).expect("Failed to read the shipped level");
+    let ron: String = ron.lines()
+        .filter(|line| !line.trim_start().starts_with("player_spawn"))
+        .map(|line| format!("{}\n", line))
+        .collect();
+    let path = env::temp_dir().join("platformer_level_no_spawn.ron");
+    fs::write(&path, ron).expect("Failed to write test level");
+
+    match Level::load(path.to_str().unwrap()) {
+        Err(error @ LevelError::Load(_)) => assert!(error.to_string().contains("player_spawn"), "{}", error),
+        other => panic!("expected a load error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_a_level_without_ground() {
+    let level = Level { ground: vec![], ..shipped_level() };
+    match level.validate() {
+        Err(LevelError::NoGround) => {},
+        other => panic!("expected a no ground error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_ground_without_a_size_by_its_index() {
+    let mut level = shipped_level();
+    level.ground.push(GroundSegment { left: 0., top: 74., width: 0., height: 128. });
+    match level.validate() {
+        Err(LevelError::InvalidGround { index: 1, .. }) => {},
+        other => panic!("expected an invalid ground error for segment 1, got {:?}", other),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,39 @@
+use amethyst_2d_platformer_demo::{
+    PLAYER_H,
+    config::{MovementConfig, MovementConfigError},
+};
+
+fn rejects(config: MovementConfig) -> MovementConfigError {
+    config.validate().expect_err(&format!("expected {:?} to be rejected", config))
+}
+
+#[test]
+fn accepts_the_shipped_config() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/resources/movement_config.ron");
+    let config = MovementConfig::load(path).unwrap();
+    assert_eq!(config, MovementConfig::default());
+}
+
+#[test]
+fn rejects_negative_gravity() {
+    let error = rejects(MovementConfig { gravity: -2520., ..MovementConfig::default() });
+    assert!(matches!(error, MovementConfigError::NotPositive { field: "gravity" }), "{:?}", error);
+}
+
+#[test]
+fn rejects_zero_max_speed() {
+    let error = rejects(MovementConfig { max_x_velocity: 0., ..MovementConfig::default() });
+    assert!(matches!(error, MovementConfigError::NotPositive { field: "max_x_velocity" }), "{:?}", error);
+}
+
+#[test]
+fn rejects_negative_jump_height() {
+    let error = rejects(MovementConfig { max_jump_height: -285., ..MovementConfig::default() });
+    assert!(matches!(error, MovementConfigError::Negative { field: "max_jump_height" }), "{:?}", error);
+}
+
+#[test]
+fn rejects_crouching_taller_than_standing() {
+    let error = rejects(MovementConfig { crouch_height: PLAYER_H as f32 + 1., ..MovementConfig::default() });
+    assert!(matches!(error, MovementConfigError::TooLarge { field: "crouch_height", .. }), "{:?}", error);
+}
\ No newline at end of file
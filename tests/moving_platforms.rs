@@ -0,0 +1,86 @@
+mod common;
+
+use amethyst::ecs::Join;
+use amethyst_2d_platformer_demo::{
+    collision::CONTACT_EPSILON,
+    components::{MovingPlatform, TwoDimObject},
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    level::{GroundSegment, Level, MovingSegment, PathMode, Point},
+    systems::InputScript,
+};
+
+use common::{floor, player, standing_at};
+
+const PLATFORM_TOP: f32 = 200.;
+
+/// The player standing on a platform which moves between the given top left corners.
+fn platform_level(from: Point, to: Point, ceiling: Option<GroundSegment>) -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![floor(0.)]
+            .into_iter()
+            .chain(ceiling)
+            .collect(),
+        moving_platforms: vec![MovingSegment {
+            width: 200.,
+            height: 20.,
+            waypoints: vec![from, to],
+            mode: PathMode::PingPong,
+            speed: 100.,
+            wait_frames: 0,
+        }],
+        player_spawn: standing_at(from.x + 100., from.y),
+        ..Level::default()
+    })
+}
+
+fn platform(simulation: &Simulation) -> TwoDimObject {
+    let world = simulation.world();
+    let two_dim_objects = world.read_storage::<TwoDimObject>();
+    let moving_platforms = world.read_storage::<MovingPlatform>();
+    let (two_dim_object, _) = (&two_dim_objects, &moving_platforms).join().next().unwrap();
+    *two_dim_object
+}
+
+#[test]
+fn rider_is_carried_sideways() {
+    let level = platform_level(Point { x: 0., y: PLATFORM_TOP }, Point { x: 400., y: PLATFORM_TOP }, None);
+    let mut simulation = Simulation::new(&level, MovementConfig::default(), InputScript::new());
+    simulation.step(10);
+    let (player_before, platform_before) = (player(&simulation), platform(&simulation));
+    simulation.step(60);
+    let (player_after, platform_after) = (player(&simulation), platform(&simulation));
+
+    let platform_moved = platform_after.left() - platform_before.left();
+    let player_moved = player_after.body.left - player_before.body.left;
+    assert!(platform_moved > 90., "expected the platform to move, it moved {}", platform_moved);
+    assert!((player_moved - platform_moved).abs() < 0.1, "player moved {}, platform moved {}", player_moved, platform_moved);
+}
+
+#[test]
+fn rider_stays_on_top_going_up_and_down() {
+    let level = platform_level(Point { x: 0., y: PLATFORM_TOP }, Point { x: 0., y: PLATFORM_TOP + 150. }, None);
+    let mut simulation = Simulation::new(&level, MovementConfig::default(), InputScript::new());
+    simulation.step(10);
+
+    // long enough to go up, turn around and come back down
+    for _ in 0..240 {
+        simulation.step(1);
+        let (player, platform) = (player(&simulation), platform(&simulation));
+        assert!((player.body.bottom - platform.top()).abs() <= CONTACT_EPSILON, "player fell off at {:?}", player);
+    }
+}
+
+#[test]
+fn platform_waits_rather_than_crushing_the_rider() {
+    // the ceiling leaves less room above the platform's highest point than the player is tall
+    let ceiling = GroundSegment { left: -1000., top: 600., width: 3000., height: 100. };
+    let level = platform_level(Point { x: 0., y: PLATFORM_TOP }, Point { x: 0., y: PLATFORM_TOP + 300. }, Some(ceiling));
+    let mut simulation = Simulation::new(&level, MovementConfig::default(), InputScript::new());
+    simulation.step(300);
+
+    let (player, platform) = (player(&simulation), platform(&simulation));
+    assert!(player.body.top <= 500. + CONTACT_EPSILON, "player was pushed into the ceiling: {:?}", player);
+    assert!((player.body.bottom - platform.top()).abs() <= CONTACT_EPSILON, "platform moved into the player: {:?}", player);
+}
\ No newline at end of file
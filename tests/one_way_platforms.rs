@@ -0,0 +1,51 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    entities::LevelSource,
+    level::{GroundSegment, Level},
+    systems::{InputScript, PlayerInput},
+};
+
+use common::{floor, idle, jump, run, standing_at};
+
+const GROUND_TOP: f32 = 74.;
+const PLATFORM_TOP: f32 = 300.;
+
+/// Ground with a one-way platform above it, the player standing on whichever `spawn_top` is.
+fn platform_level(spawn_top: f32) -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![floor(GROUND_TOP)],
+        one_way_platforms: vec![GroundSegment { left: 0., top: PLATFORM_TOP, width: 1280., height: 20. }],
+        player_spawn: standing_at(500., spawn_top),
+        ..Level::default()
+    })
+}
+
+#[test]
+fn jumping_up_through_a_platform_lands_on_top_of_it() {
+    let script = InputScript::new().hold(jump(), 60);
+
+    let player = run(&platform_level(GROUND_TOP), script, 120);
+    assert_eq!(player.body.bottom, PLATFORM_TOP);
+    assert_eq!(player.body.velocity_y, 0.);
+}
+
+#[test]
+fn down_and_jump_drops_through_a_platform() {
+    let script = InputScript::new()
+        .hold(idle(), 5)
+        .hold(PlayerInput { y: -1., jump: true, ..PlayerInput::default() }, 1);
+
+    let player = run(&platform_level(PLATFORM_TOP), script, 60);
+    assert_eq!(player.body.bottom, GROUND_TOP);
+}
+
+#[test]
+fn jump_alone_does_not_drop_through_a_platform() {
+    let script = InputScript::new()
+        .hold(idle(), 5)
+        .hold(jump(), 1);
+
+    let player = run(&platform_level(PLATFORM_TOP), script, 120);
+    assert_eq!(player.body.bottom, PLATFORM_TOP);
+}
\ No newline at end of file
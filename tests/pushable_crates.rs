@@ -0,0 +1,81 @@
+mod common;
+
+use amethyst::ecs::Join;
+use amethyst_2d_platformer_demo::{
+    CRATE_SIZE,
+    collision::CONTACT_EPSILON,
+    components::{Pushable, TwoDimObject},
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    level::{Crate, GroundSegment, Level},
+    systems::InputScript,
+};
+
+use common::{player, right, standing_at};
+
+const GROUND_TOP: f32 = 100.;
+
+fn floor() -> GroundSegment {
+    common::floor(GROUND_TOP)
+}
+
+fn pushable_crate(left: f32, bottom: f32) -> Crate {
+    Crate { left, bottom, pushable: true }
+}
+
+/// The player standing at x = 100 with their feet at `spawn_bottom`, about to walk right for `frames`
+/// frames.
+fn walking_right(ground: Vec<GroundSegment>, crates: Vec<Crate>, spawn_bottom: f32, frames: usize) -> Simulation {
+    let level = LevelSource::Level(Level {
+        ground,
+        crates,
+        player_spawn: standing_at(100., spawn_bottom),
+        ..Level::default()
+    });
+    let script = InputScript::new().hold(right(), frames);
+    Simulation::new(&level, MovementConfig::default(), script)
+}
+
+/// Pushable crates in the order they are listed in the level.
+fn crates(simulation: &Simulation) -> Vec<TwoDimObject> {
+    let world = simulation.world();
+    let (pushables, two_dim_objects) = (world.read_storage::<Pushable>(), world.read_storage::<TwoDimObject>());
+    (&pushables, &two_dim_objects).join().map(|(_, two_dim_object)| *two_dim_object).collect()
+}
+
+#[test]
+fn walking_into_a_stack_of_crates_pushes_the_whole_stack() {
+    let stack = vec![pushable_crate(200., GROUND_TOP), pushable_crate(200., GROUND_TOP + CRATE_SIZE)];
+    let mut simulation = walking_right(vec![floor()], stack, GROUND_TOP, 120);
+    simulation.step(120);
+
+    let (player, crates) = (player(&simulation), crates(&simulation));
+    let (bottom, top) = (crates[0], crates[1]);
+    assert!(bottom.left() > 300., "expected the crates to be pushed, got {}", bottom.left());
+    assert!((bottom.left() - player.body.right).abs() <= CONTACT_EPSILON, "player isn't up against the crate: {:?}", player);
+    assert!((top.left() - bottom.left()).abs() <= CONTACT_EPSILON, "stack came apart at {} and {}", bottom.left(), top.left());
+    assert!((top.bottom() - bottom.top()).abs() <= CONTACT_EPSILON);
+}
+
+#[test]
+fn crate_pushed_off_a_ledge_falls_to_the_ground_below() {
+    let ledge = GroundSegment { left: -1000., top: 300., width: 1300., height: 200. };
+    let mut simulation = walking_right(vec![ledge, floor()], vec![pushable_crate(200., ledge.top)], ledge.top, 90);
+    simulation.step(240);
+
+    let crate_ = crates(&simulation)[0];
+    assert!(crate_.left() > 300., "crate is still on the ledge at {}", crate_.left());
+    assert!((crate_.bottom() - GROUND_TOP).abs() <= CONTACT_EPSILON, "crate didn't land, it's at {}", crate_.bottom());
+}
+
+#[test]
+fn crate_against_a_wall_blocks_the_player() {
+    let wall = GroundSegment { left: 400., top: 400., width: 100., height: 300. };
+    let mut simulation = walking_right(vec![floor(), wall], vec![pushable_crate(250., GROUND_TOP)], GROUND_TOP, 180);
+    simulation.step(180);
+
+    let (player, crate_) = (player(&simulation), crates(&simulation)[0]);
+    assert!((crate_.right() - wall.left).abs() <= CONTACT_EPSILON, "crate stopped at {}", crate_.right());
+    assert!((player.body.right - crate_.left()).abs() <= CONTACT_EPSILON, "player stopped at {:?}", player);
+}
\ No newline at end of file
@@ -0,0 +1,54 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    collision::CONTACT_EPSILON,
+    components::{RigidBody, Velocity},
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    systems::InputScript,
+};
+
+use common::{add_body, add_projectile, body, flat_level};
+
+const BOX_SIZE: f32 = 50.;
+const GROUND_TOP: f32 = 100.;
+
+/// Flat ground, with the player parked out of the way of the bodies.
+fn level() -> LevelSource {
+    LevelSource::Level(flat_level(GROUND_TOP, -900.))
+}
+
+#[test]
+fn body_falls_onto_the_ground_and_rests_there() {
+    let mut simulation = Simulation::new(&level(), MovementConfig::default(), InputScript::new());
+    let box_ = add_body(&mut simulation, BOX_SIZE, 0., 400., Velocity::default(), RigidBody::default());
+    simulation.step(120);
+
+    let body = body(&simulation, box_);
+    assert_eq!(body.bottom(), GROUND_TOP);
+    assert!(body.contacts.on_ground());
+}
+
+#[test]
+fn weightless_body_flies_straight() {
+    let mut simulation = Simulation::new(&level(), MovementConfig::default(), InputScript::new());
+    let projectile = add_projectile(&mut simulation, BOX_SIZE, 0., 400., Velocity { x: 600., y: 0. });
+    simulation.step(60);
+
+    let body = body(&simulation, projectile);
+    assert_eq!(body.bottom(), 400.);
+    assert!((body.left() - 600.).abs() < 1., "expected the projectile to fly 600 units, got {}", body.left());
+}
+
+#[test]
+fn moving_bodies_stop_against_each_other() {
+    let mut simulation = Simulation::new(&level(), MovementConfig::default(), InputScript::new());
+    let left = add_body(&mut simulation, BOX_SIZE, 0., GROUND_TOP, Velocity { x: 200., y: 0. }, RigidBody::default());
+    let right = add_body(&mut simulation, BOX_SIZE, 300., GROUND_TOP, Velocity { x: -200., y: 0. }, RigidBody::default());
+    simulation.step(120);
+
+    let (left, right) = (body(&simulation, left), body(&simulation, right));
+    assert!((right.left() - left.right()).abs() <= CONTACT_EPSILON, "expected the bodies to touch, got {} and {}", left.right(), right.left());
+    assert!(left.contacts.right.is_some() && right.contacts.left.is_some());
+}
\ No newline at end of file
@@ -0,0 +1,87 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    collision::CONTACT_EPSILON,
+    components::PlayerState,
+    config::MovementConfig,
+    entities::LevelSource,
+    headless::Simulation,
+    level::{GroundSegment, Level, Point, SlopeSegment},
+    systems::InputScript,
+};
+
+use common::{floor, moving, player, standing_at};
+
+const GROUND_TOP: f32 = 100.;
+const PLATEAU_TOP: f32 = 300.;
+
+/// Ground with a 45° slope up to a plateau, and a shallower slope back down the other side.
+fn hill_level(spawn: Point) -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![
+            GroundSegment { left: -1000., top: GROUND_TOP, width: 5000., height: 100. },
+            GroundSegment { left: 400., top: PLATEAU_TOP, width: 400., height: PLATEAU_TOP - GROUND_TOP },
+        ],
+        slopes: vec![
+            SlopeSegment { left: 200., bottom: GROUND_TOP, width: 200., left_height: 0., right_height: 200. },
+            SlopeSegment { left: 800., bottom: GROUND_TOP, width: 400., left_height: 200., right_height: 0. },
+        ],
+        player_spawn: spawn,
+        ..Level::default()
+    })
+}
+
+fn on_ground_at(x: f32) -> Point {
+    standing_at(x, GROUND_TOP)
+}
+
+fn walk(x: f32, frames: usize) -> InputScript {
+    InputScript::new().hold(moving(x, 0.), frames)
+}
+
+#[test]
+fn walking_over_a_hill_never_leaves_the_ground() {
+    let mut simulation = Simulation::new(&hill_level(on_ground_at(100.)), MovementConfig::default(), walk(1., 480));
+
+    for _ in 0..480 {
+        simulation.step(1);
+        let player = player(&simulation);
+        assert!(
+            player.state == PlayerState::Walking || player.state == PlayerState::Running,
+            "player left the ground at {:?}", player,
+        );
+        assert_eq!(player.body.velocity_y, 0.);
+    }
+
+    let player = player(&simulation);
+    assert!(player.body.left > 1200., "player didn't make it over the hill: {:?}", player);
+    assert!((player.body.bottom - GROUND_TOP).abs() <= CONTACT_EPSILON);
+}
+
+#[test]
+fn falling_onto_a_slope_lands_on_it_under_the_middle_of_the_body() {
+    let spawn = Point { x: 300., y: 500. };
+    let mut simulation = Simulation::new(&hill_level(spawn), MovementConfig::default(), InputScript::new());
+    simulation.step(120);
+
+    // halfway along the 45° slope
+    let player = player(&simulation);
+    assert!((player.body.bottom - (GROUND_TOP + 100.)).abs() <= CONTACT_EPSILON, "landed at {:?}", player);
+    assert_eq!(player.state, PlayerState::Idle);
+}
+
+#[test]
+fn high_end_of_a_slope_is_a_wall() {
+    let level = LevelSource::Level(Level {
+        ground: vec![floor(GROUND_TOP)],
+        slopes: vec![SlopeSegment { left: 200., bottom: GROUND_TOP, width: 200., left_height: 0., right_height: 200. }],
+        player_spawn: on_ground_at(600.),
+        ..Level::default()
+    });
+    let mut simulation = Simulation::new(&level, MovementConfig::default(), walk(-1., 120));
+    simulation.step(120);
+
+    let player = player(&simulation);
+    assert!((player.body.left - 400.).abs() <= CONTACT_EPSILON, "player walked into the slope: {:?}", player);
+    assert!((player.body.bottom - GROUND_TOP).abs() <= CONTACT_EPSILON);
+}
\ No newline at end of file
@@ -0,0 +1,118 @@
+use std::{env, fs};
+
+use amethyst_2d_platformer_demo::tiled::{TiledError, TiledMap};
+
+/// A one-tile map with the given layers, and a tileset of a single tile.
+fn map_json(layers: &str) -> String {
+    format!(r#"{{
+        "width": 2, "height": 1, "tilewidth": 128, "tileheight": 128,
+        "tilesets": [{{
+            "firstgid": 1, "image": "ground.png", "imagewidth": 128, "imageheight": 128,
+            "tilewidth": 128, "tileheight": 128, "columns": 1, "tilecount": 1
+        }}],
+        "layers": [{}]
+    }}"#, layers)
+}
+
+const SPAWN_LAYER: &str = r#"{
+    "type": "objectgroup", "name": "objects",
+    "objects": [{ "name": "spawn", "x": 64, "y": 64 }]
+}"#;
+
+/// Writes the map to a file of its own in the temp directory and loads it.
+fn load(file_name: &str, json: &str) -> Result<TiledMap, TiledError> {
+    let path = env::temp_dir().join(format!("platformer_tiled_{}", file_name));
+    fs::write(&path, json).expect("Failed to write test map");
+    TiledMap::load(path.to_str().unwrap())
+}
+
+#[test]
+fn loads_the_demo_map() {
+    let map = TiledMap::load("resources/maps/demo.json").expect("Failed to load the demo map");
+
+    // a run of tiles along the floor and one floating, the wall object, and the one-way ledge
+    assert_eq!(map.colliders.len(), 4);
+    assert_eq!(map.colliders.iter().filter(|collider| collider.one_way).count(), 1);
+    assert_eq!((map.player_spawn.x, map.player_spawn.y), (200., 624.));
+
+    assert_eq!(map.slopes.len(), 1);
+    let slope = map.slopes[0];
+    assert_eq!((slope.left, slope.bottom, slope.width), (512., 128., 256.));
+    assert_eq!((slope.left_height, slope.right_height), (0., 128.));
+
+    assert_eq!(map.climbables.len(), 1);
+    let ladder = map.climbables[0];
+    assert_eq!((ladder.left, ladder.top, ladder.width, ladder.height), (1160., 512., 64., 384.));
+}
+
+#[test]
+fn rejects_a_map_without_a_spawn() {
+    let layer = r#"{ "type": "tilelayer", "name": "ground", "width": 2, "height": 1, "data": [1, 1] }"#;
+    match load("no_spawn.json", &map_json(layer)) {
+        Err(TiledError::MissingSpawn) => {},
+        other => panic!("expected a missing spawn error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_a_tile_from_no_tileset() {
+    let layer = r#"{ "type": "tilelayer", "name": "ground", "width": 2, "height": 1, "data": [1, 5] }"#;
+    match load("unknown_gid.json", &map_json(&format!("{}, {}", layer, SPAWN_LAYER))) {
+        Err(TiledError::UnknownTile { gid: 5, .. }) => {},
+        other => panic!("expected an unknown tile error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_an_unsupported_layer() {
+    let layer = r#"{ "type": "imagelayer", "name": "sky", "image": "sky.png" }"#;
+    match load("image_layer.json", &map_json(&format!("{}, {}", layer, SPAWN_LAYER))) {
+        Err(TiledError::UnsupportedLayer { ref layer, ref kind }) if layer == "sky" && kind == "imagelayer" => {},
+        other => panic!("expected an unsupported layer error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_a_tile_layer_with_no_width() {
+    let layer = r#"{ "type": "tilelayer", "name": "ground", "width": 0, "height": 1, "data": [1, 1] }"#;
+    match load("zero_width.json", &map_json(&format!("{}, {}", layer, SPAWN_LAYER))) {
+        Err(TiledError::InvalidLayerSize { .. }) => {},
+        other => panic!("expected an invalid layer size error, got {:?}", other),
+    }
+}
+
+#[test]
+fn loads_the_demo_map_saved_as_tmx() {
+    let tmx = TiledMap::load("resources/maps/demo.tmx").expect("Failed to load the TMX demo map");
+    let json = TiledMap::load("resources/maps/demo.json").expect("Failed to load the demo map");
+    assert_eq!(format!("{:?}", tmx), format!("{:?}", json));
+}
+
+#[test]
+fn rejects_base64_tile_data_in_tmx_maps() {
+    let tmx = r#"<map width="2" height="1" tilewidth="128" tileheight="128">
+        <layer name="ground" width="2" height="1"><data encoding="base64">AQAAAAEAAAA=</data></layer>
+    </map>"#;
+    match load("base64.tmx", tmx) {
+        Err(TiledError::UnsupportedEncoding { ref encoding, .. }) if encoding == "base64" => {},
+        other => panic!("expected an unsupported encoding error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_a_tileset_with_no_columns() {
+    let json = map_json(SPAWN_LAYER).replace(r#""columns": 1"#, r#""columns": 0"#);
+    match load("zero_columns.json", &json) {
+        Err(TiledError::InvalidTilesetField { field: "columns", .. }) => {},
+        other => panic!("expected an invalid tileset error, got {:?}", other),
+    }
+}
+
+#[test]
+fn rejects_a_map_too_large_to_measure() {
+    let json = map_json(SPAWN_LAYER).replace(r#""width": 2"#, r#""width": 4294967295"#);
+    match load("too_large.json", &json) {
+        Err(TiledError::MapTooLarge { .. }) => {},
+        other => panic!("expected a map too large error, got {:?}", other),
+    }
+}
\ No newline at end of file
@@ -0,0 +1,62 @@
+mod common;
+
+use amethyst_2d_platformer_demo::{
+    PLAYER_W,
+    components::PlayerState,
+    config::MovementConfig,
+    entities::LevelSource,
+    level::{GroundSegment, Level},
+    systems::InputScript,
+};
+
+use common::{idle, right, right_jump, run, standing_at};
+
+const GROUND_TOP: f32 = 74.;
+const WALL_LEFT: f32 = 600.;
+
+/// A tall wall, with the player in the air just touching its left side.
+fn wall_level() -> LevelSource {
+    LevelSource::Level(Level {
+        ground: vec![
+            GroundSegment { left: -1000., top: GROUND_TOP, width: 3000., height: 100. },
+            GroundSegment { left: WALL_LEFT, top: 1000., width: 100., height: 1000. - GROUND_TOP },
+        ],
+        player_spawn: standing_at(WALL_LEFT - PLAYER_W as f32 / 2., 700.),
+        ..Level::default()
+    })
+}
+
+#[test]
+fn pushing_into_a_wall_while_falling_slides_down_it() {
+    let movement = MovementConfig::default();
+    let player = run(&wall_level(), InputScript::new().hold(right(), 30), 30);
+    assert_eq!(player.state, PlayerState::WallSlide);
+    assert_eq!(player.body.velocity_y, -movement.wall_slide_velocity, "player ended at {:?}", player);
+}
+
+#[test]
+fn falling_beside_a_wall_without_pushing_into_it_is_not_slowed() {
+    let movement = MovementConfig::default();
+    let player = run(&wall_level(), InputScript::new().hold(idle(), 30), 30);
+    assert_eq!(player.state, PlayerState::Falling);
+    assert!(player.body.velocity_y < -movement.wall_slide_velocity, "player ended at {:?}", player);
+}
+
+#[test]
+fn jumping_off_a_wall_pushes_away_and_ignores_input_for_a_moment() {
+    let movement = MovementConfig::default();
+    let lock_frames = movement.wall_jump_lock_frames as usize;
+    let script = InputScript::new()
+        .hold(right(), 20)
+        .hold(right_jump(), 1)
+        .hold(right(), lock_frames);
+
+    // still pushed away from the wall on the last frame holding right is ignored
+    let player = run(&wall_level(), script.clone(), 20 + lock_frames);
+    assert_eq!(player.body.velocity_x, -movement.wall_jump_x_velocity, "player ended at {:?}", player);
+    assert!(player.body.velocity_y > 0., "player ended at {:?}", player);
+
+    // and back under the player's control on the next
+    let player = run(&wall_level(), script, 21 + lock_frames);
+    assert!(player.body.velocity_x > -movement.wall_jump_x_velocity, "player ended at {:?}", player);
+}
\ No newline at end of file